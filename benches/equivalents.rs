@@ -0,0 +1,65 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Planning with many equivalent actions, with and without deduping them.
+
+#[macro_use]
+extern crate criterion;
+extern crate rgoap;
+
+use criterion::{BatchSize, Criterion};
+use rgoap::{Action, Planner, State, plan_with_stats};
+
+/// The number of goal atoms of the domain.
+const ATOM_COUNT: usize = 8;
+
+/// The number of equivalent actions fixing each goal atom.
+const EQUIVALENT_COUNT: usize = 8;
+
+/// Makes a domain where each goal atom can be fixed by several actions doing exactly the same, so
+/// that each expanded node has that many times the successors it needs.
+fn domain() -> (State, State, Vec<Action>) {
+    let mut initial_state = State::new();
+    let mut goal_state = State::new();
+    let mut actions = vec![];
+    for atom in 0..ATOM_COUNT {
+        initial_state.insert(format!("atom_{}", atom), false.into());
+        goal_state.insert(format!("atom_{}", atom), true.into());
+        for way in 0..EQUIVALENT_COUNT {
+            actions.push(Action::new(format!("fix_{}_way_{}", atom, way), 1)
+                .produces(format!("atom_{}", atom), true));
+        }
+    }
+
+    (initial_state, goal_state, actions)
+}
+
+fn bench_equivalents(c: &mut Criterion) {
+    let (initial_state, goal_state, actions) = domain();
+
+    // Deduping leaves the expanded nodes as they are, but generates a single successor per atom.
+    let (_, stats) = plan_with_stats(&initial_state, &goal_state, &actions);
+    let deduped: Vec<Action> = actions.iter().step_by(EQUIVALENT_COUNT).cloned().collect();
+    let (_, deduped_stats) = plan_with_stats(&initial_state, &goal_state, &deduped);
+    println!("equivalents: {} nodes expanded, {} generated without deduping, {} with it",
+             stats.expanded,
+             stats.generated,
+             deduped_stats.generated);
+
+    // Each plan is made by a new planner, which doesn't remember any last plan yet.
+    let mut group = c.benchmark_group("equivalents");
+    for &(name, dedupe) in &[("kept", false), ("deduped", true)] {
+        let new_planner = || {
+            let planner = Planner::new(actions.clone());
+            if dedupe { planner.with_equivalents_deduped() } else { planner }
+        };
+        group.bench_function(name, |b| {
+            b.iter_batched(&new_planner,
+                           |planner| planner.plan(&initial_state, &goal_state).unwrap().len(),
+                           BatchSize::SmallInput)
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_equivalents);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Expanding many nodes of states of many atoms, most of which no action changes.
+//!
+//! Each search goes through every combination of the switches before paying for the expensive way
+//! to the goal, so the time it takes is mostly that of expanding nodes and reaching their children.
+
+#[macro_use]
+extern crate criterion;
+extern crate rgoap;
+
+use criterion::{BenchmarkId, Criterion, Throughput};
+use rgoap::{Action, State, plan};
+
+/// The number of switches, every combination of which is expanded.
+const SWITCH_COUNT: usize = 10;
+
+/// The numbers of atoms no action changes.
+const UNTOUCHED_COUNTS: [usize; 3] = [0, 100, 1_000];
+
+/// Makes a domain in which flipping switches is cheap but never helps, and getting ready to reach
+/// the goal costs more than flipping all of them.
+fn domain(untouched_count: usize) -> (State, State, Vec<Action>) {
+    let mut initial_state = State::new();
+    for atom in 0..untouched_count {
+        initial_state.insert(format!("untouched_{}", atom), (atom % 2 == 0).into());
+    }
+    let mut actions = vec![];
+    for switch in 0..SWITCH_COUNT {
+        initial_state.insert(format!("switch_{}", switch), false.into());
+        actions.push(Action::new(format!("flip_{}", switch), 1)
+            .produces(format!("switch_{}", switch), true));
+    }
+    initial_state.insert("ready".to_string(), false.into());
+    initial_state.insert("done".to_string(), false.into());
+    actions.push(Action::new("prepare", 2 * SWITCH_COUNT).produces("ready", true));
+    actions.push(Action::new("finish", 1).requires("ready", true).produces("done", true));
+
+    let mut goal_state = State::new();
+    goal_state.insert("done".to_string(), true.into());
+
+    (initial_state, goal_state, actions)
+}
+
+fn bench_expansion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("expansion");
+    group.sample_size(20);
+    // Reports how many nodes are expanded per second.
+    group.throughput(Throughput::Elements(1 << SWITCH_COUNT));
+    for &untouched_count in &UNTOUCHED_COUNTS {
+        let (initial_state, goal_state, actions) = domain(untouched_count);
+        group.bench_function(BenchmarkId::new("plan", untouched_count), |b| {
+            b.iter(|| plan(&initial_state, &goal_state, &actions).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_expansion);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Planning with states of many atoms, most of which no action changes.
+//!
+//! Nodes are told apart by the atoms differing from the initial state only, so the search itself
+//! takes about as long whatever the number of untouched atoms, unlike hashing full states would.
+//! Only setting the search up, which goes through the initial state once, takes longer.
+
+#[macro_use]
+extern crate criterion;
+extern crate rgoap;
+
+use criterion::{BenchmarkId, Criterion};
+use rgoap::{Action, State, hash_state, plan};
+
+/// The numbers of atoms no action changes.
+const UNTOUCHED_COUNTS: [usize; 3] = [0, 10_000, 50_000];
+
+/// Makes a domain with a short chain of actions to plan, among many untouched atoms.
+fn domain(untouched_count: usize) -> (State, State, Vec<Action>) {
+    let mut initial_state = State::new();
+    for atom in 0..untouched_count {
+        initial_state.insert(format!("untouched_{}", atom), (atom % 2 == 0).into());
+    }
+    let mut actions = vec![];
+    for step in 0..8 {
+        initial_state.insert(format!("step_{}", step), false.into());
+        let mut action = Action::new(format!("do_step_{}", step), 1)
+            .produces(format!("step_{}", step), true);
+        if step > 0 {
+            action = action.requires(format!("step_{}", step - 1), true);
+        }
+        actions.push(action);
+    }
+    let mut goal_state = State::new();
+    goal_state.insert("step_7".to_string(), true.into());
+
+    (initial_state, goal_state, actions)
+}
+
+fn bench_large_states(c: &mut Criterion) {
+    let mut group = c.benchmark_group("large_states");
+    for &untouched_count in &UNTOUCHED_COUNTS {
+        let (initial_state, goal_state, actions) = domain(untouched_count);
+        group.bench_function(BenchmarkId::new("plan", untouched_count), |b| {
+            b.iter(|| plan(&initial_state, &goal_state, &actions).unwrap())
+        });
+
+        // What hashing each node's full state would cost, for each of the nodes of the search.
+        group.bench_function(BenchmarkId::new("hash_full_state", untouched_count), |b| {
+            b.iter(|| hash_state(&initial_state))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_large_states);
+criterion_main!(benches);
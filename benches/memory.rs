@@ -0,0 +1,68 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Peak memory used by the nodes of a search, which depends on how their states are stored.
+//!
+//! Run it with and without the `im` feature to compare copied changes with shared ones.
+
+extern crate rgoap;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use rgoap::{Action, State, TieBias, plan_with_tie_bias};
+
+/// An allocator keeping track of the most memory allocated at once.
+struct PeakAlloc {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = self.current.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+        self.peak.fetch_max(current, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.current.fetch_sub(layout.size(), Ordering::SeqCst);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: PeakAlloc = PeakAlloc {
+    current: AtomicUsize::new(0),
+    peak: AtomicUsize::new(0),
+};
+
+/// The numbers of goal atoms of the plateau domains.
+const ATOM_COUNTS: [usize; 3] = [8, 12, 16];
+
+/// Makes a domain where each action fixes a single goal atom, among other atoms which never
+/// change, so that preferring the start expands every combination of fixed goal atoms.
+fn domain(atom_count: usize) -> (State, State, Vec<Action>) {
+    let mut initial_state = State::new();
+    let mut goal_state = State::new();
+    let mut actions = vec![];
+    for atom in 0..atom_count {
+        initial_state.insert(format!("fixed_{}", atom), true.into());
+        initial_state.insert(format!("atom_{}", atom), false.into());
+        goal_state.insert(format!("atom_{}", atom), true.into());
+        actions.push(Action::new(format!("fix_{}", atom), 1)
+            .produces(format!("atom_{}", atom), true));
+    }
+
+    (initial_state, goal_state, actions)
+}
+
+fn main() {
+    let states = if cfg!(feature = "im") { "shared" } else { "copied" };
+    for &atom_count in &ATOM_COUNTS {
+        let (initial_state, goal_state, actions) = domain(atom_count);
+        let before = ALLOC.current.load(Ordering::SeqCst);
+        ALLOC.peak.store(before, Ordering::SeqCst);
+        plan_with_tie_bias(&initial_state, &goal_state, &actions, TieBias::PreferStart).unwrap();
+        let peak = ALLOC.peak.load(Ordering::SeqCst) - before;
+        println!("memory/{}/{} atoms: {} KiB peak", states, atom_count, peak / 1024);
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Planning over generated domains of increasing sizes.
+
+#[macro_use]
+extern crate criterion;
+extern crate rgoap;
+
+use criterion::{BenchmarkId, Criterion};
+use rgoap::{generate_domain, plan, plan_dense};
+
+/// The sizes of the generated domains, in numbers of atoms and actions.
+const SIZES: [(&str, usize, usize); 3] =
+    [("small", 8, 20), ("medium", 32, 200), ("large", 64, 1000)];
+
+fn bench_planning(c: &mut Criterion) {
+    let mut group = c.benchmark_group("plan");
+    for &(name, num_atoms, num_actions) in &SIZES {
+        let (initial_state, goal_state, actions) = generate_domain(num_atoms, num_actions, 42);
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| plan(&initial_state, &goal_state, &actions).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_dense_planning(c: &mut Criterion) {
+    let mut group = c.benchmark_group("plan_dense");
+    for &(name, num_atoms, num_actions) in &SIZES {
+        let (initial_state, goal_state, actions) = generate_domain(num_atoms, num_actions, 42);
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| plan_dense(&initial_state, &goal_state, &actions).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_planning, bench_dense_planning);
+criterion_main!(benches);
@@ -0,0 +1,55 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Planning with a large set of actions, most of which apply to any state.
+
+#[macro_use]
+extern crate criterion;
+extern crate rgoap;
+
+use criterion::Criterion;
+use rgoap::{Action, State, plan};
+
+/// The number of actions of the synthetic domain.
+const ACTION_COUNT: usize = 2000;
+
+/// Makes a domain where getting to the goal takes a chain of actions, each of them competing with
+/// many more actions applicable at every step.
+fn domain() -> (State, State, Vec<Action>) {
+    let chain_length = 10;
+
+    let mut initial_state = State::new();
+    for step in 0..chain_length {
+        initial_state.insert(format!("step_{}", step), false.into());
+    }
+
+    let mut goal_state = State::new();
+    goal_state.insert(format!("step_{}", chain_length - 1), true.into());
+
+    let mut actions = vec![];
+    for step in 0..chain_length {
+        let mut action = Action::new(format!("do_step_{}", step), 1)
+            .produces(format!("step_{}", step), true);
+        if step > 0 {
+            action = action.requires(format!("step_{}", step - 1), true);
+        }
+        actions.push(action);
+    }
+
+    // Distractions are always applicable, but costly enough to never be worth it.
+    for distraction in actions.len()..ACTION_COUNT {
+        actions.push(Action::new(format!("distraction_{}", distraction), 100)
+            .produces(format!("distracted_{}", distraction), true));
+    }
+
+    (initial_state, goal_state, actions)
+}
+
+fn bench_successors(c: &mut Criterion) {
+    let (initial_state, goal_state, actions) = domain();
+    c.bench_function("plan_2000_actions", |b| {
+        b.iter(|| plan(&initial_state, &goal_state, &actions).unwrap())
+    });
+}
+
+criterion_group!(benches, bench_successors);
+criterion_main!(benches);
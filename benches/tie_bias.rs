@@ -0,0 +1,45 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Planning over a plateau, where all nodes share the same estimated total cost.
+
+#[macro_use]
+extern crate criterion;
+extern crate rgoap;
+
+use criterion::Criterion;
+use rgoap::{Action, State, TieBias, plan_with_tie_bias};
+
+/// The number of goal atoms of the plateau domain.
+const ATOM_COUNT: usize = 10;
+
+/// Makes a domain where each action fixes a single goal atom, so that every way to the goal costs
+/// exactly as much as estimated from the start.
+///
+/// Preferring the goal expands one node per goal atom, while preferring the start expands every
+/// combination of fixed atoms, that is 1023 nodes instead of 10.
+fn domain() -> (State, State, Vec<Action>) {
+    let mut goal_state = State::new();
+    let mut actions = vec![];
+    for atom in 0..ATOM_COUNT {
+        goal_state.insert(format!("atom_{}", atom), true.into());
+        actions.push(Action::new(format!("fix_{}", atom), 1)
+            .produces(format!("atom_{}", atom), true));
+    }
+
+    (State::new(), goal_state, actions)
+}
+
+fn bench_tie_bias(c: &mut Criterion) {
+    let (initial_state, goal_state, actions) = domain();
+    let mut group = c.benchmark_group("tie_bias");
+    for &(name, bias) in &[("prefer_goal", TieBias::PreferGoal),
+                           ("prefer_start", TieBias::PreferStart)] {
+        group.bench_function(name, |b| {
+            b.iter(|| plan_with_tie_bias(&initial_state, &goal_state, &actions, bias).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_tie_bias);
+criterion_main!(benches);
@@ -0,0 +1,167 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Anytime planning, which finds a plan quickly and then keeps looking for cheaper ones.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use search::{Changes, SearchGoal, SearchSpace};
+use {Action, Atom, State, ToGoal};
+
+/// An iterator over plans of decreasing cost, as returned by `plan_anytime`.
+pub struct AnytimePlans<'a, K: 'a + Atom = String> {
+    space: SearchSpace<'a, K>,
+    goal: SearchGoal,
+    allowed_actions: &'a [Action<K>],
+    records: Vec<Record>,
+    open: BinaryHeap<Reverse<(usize, usize, usize)>>,
+    best_g_costs: HashMap<Changes, usize>,
+    best_cost: Option<usize>,
+}
+
+/// A node reached by the search, along with the way it was reached.
+///
+/// Records are never updated: reaching a node in a cheaper way makes a new record, so that the
+/// path to each record always is the one its cost in the open set was computed with.
+struct Record {
+    changes: Changes,
+    parent: Option<(usize, usize)>,
+}
+
+/// Formulates plans to get from an initial state to a goal state using a set of allowed actions,
+/// and returns them in order of decreasing cost, along with their cost.
+///
+/// The search heads straight toward the goal state to find a first plan quickly. It then keeps
+/// going, only looking for plans cheaper than the last one found, until there are none left. The
+/// caller can stop pulling plans at any time, such as when it's out of time, and use the last plan
+/// yielded. As long as `plan` would be optimal, the last plan yielded before the iterator is
+/// exhausted is the optimal one.
+pub fn plan_anytime<'a, G, K>(initial_state: &'a State<K>,
+                              goal_state: &G,
+                              allowed_actions: &'a [Action<K>])
+                              -> AnytimePlans<'a, K>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    let mut plans = AnytimePlans {
+        space,
+        goal,
+        allowed_actions,
+        records: vec![],
+        open: BinaryHeap::new(),
+        best_g_costs: HashMap::new(),
+        best_cost: None,
+    };
+    plans.push(Changes::new(), None, 0);
+
+    plans
+}
+
+impl<'a, K: Atom> AnytimePlans<'a, K> {
+    /// Records a newly reached node and adds it to the open set, unless it was already reached in
+    /// a way that's at most as expensive, or it can't lead to a plan cheaper than the best one.
+    fn push(&mut self, changes: Changes, parent: Option<(usize, usize)>, g_cost: usize) {
+        if let Some(&best_g_cost) = self.best_g_costs.get(&changes) {
+            if best_g_cost <= g_cost {
+                return;
+            }
+        }
+
+        let h_cost = self.space.node(changes.clone()).mismatch_count(&self.goal);
+        if !self.may_improve(g_cost + h_cost) {
+            return;
+        }
+
+        // Nodes the closest to the goal are expanded first, and the cheapest ones among those.
+        self.best_g_costs.insert(changes.clone(), g_cost);
+        self.open.push(Reverse((h_cost, g_cost, self.records.len())));
+        self.records.push(Record { changes, parent });
+    }
+
+    /// Returns `true` if a node with the given estimated total cost may lead to a plan cheaper than
+    /// the best one found so far.
+    fn may_improve(&self, f_cost: usize) -> bool {
+        match self.best_cost {
+            Some(best_cost) => f_cost < best_cost,
+            None => true,
+        }
+    }
+
+    /// Returns the actions leading to a record.
+    fn actions_to(&self, mut index: usize) -> Vec<&'a Action<K>> {
+        let mut actions = vec![];
+        while let Some((parent, action_index)) = self.records[index].parent {
+            actions.push(&self.allowed_actions[action_index]);
+            index = parent;
+        }
+
+        actions.reverse();
+        actions
+    }
+}
+
+impl<'a, K: Atom> Iterator for AnytimePlans<'a, K> {
+    type Item = (Vec<&'a Action<K>>, usize);
+
+    fn next(&mut self) -> Option<(Vec<&'a Action<K>>, usize)> {
+        while let Some(Reverse((h_cost, g_cost, index))) = self.open.pop() {
+            // Skips the nodes reached in a cheaper way since, or which can no longer lead to a
+            // plan cheaper than the best one.
+            if self.best_g_costs[&self.records[index].changes] < g_cost ||
+               !self.may_improve(g_cost + h_cost) {
+                continue;
+            }
+
+            // Not a single goal atom is left unfulfilled, so the goal state is reached.
+            if h_cost == 0 {
+                self.best_cost = Some(g_cost);
+                return Some((self.actions_to(index), g_cost));
+            }
+
+            let transitions: Vec<_> = self.space
+                .node(self.records[index].changes.clone())
+                .transitions()
+                .into_iter()
+                .map(|(action_index, child, cost)| (action_index, child.changes().clone(), cost))
+                .collect();
+            for (action_index, changes, cost) in transitions {
+                self.push(changes, Some((index, action_index)), g_cost + cost);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plan_with_cost;
+    use tests::key_and_door;
+
+    #[test]
+    fn test_plan_anytime() {
+        // The greedy first pass smashes the door in one step, and the next one finds the cheaper
+        // way with the key.
+        let (initial_state, goal_state, actions) = key_and_door();
+        let plans: Vec<(Vec<&str>, usize)> = plan_anytime(&initial_state, &goal_state, &actions)
+            .map(|(actions, cost)| {
+                (actions.iter().map(|action| action.name.as_str()).collect(), cost)
+            })
+            .collect();
+        assert_eq!(plans, vec![(vec!["smash_door"], 5), (vec!["get_key", "open_door"], 2)]);
+
+        let optimal_plan = plan_with_cost(&initial_state, &goal_state, &actions).unwrap();
+        let (last_plan, last_cost) =
+            plan_anytime(&initial_state, &goal_state, &actions).last().unwrap();
+        assert_eq!(last_plan, optimal_plan.actions);
+        assert_eq!(last_cost, optimal_plan.cost);
+
+        // There's nothing to yield when there's no plan at all.
+        let mut goal_state = State::new();
+        goal_state.insert("window_open".to_string(), true.into());
+        assert_eq!(plan_anytime(&initial_state, &goal_state, &actions).next(), None);
+    }
+}
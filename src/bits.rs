@@ -0,0 +1,344 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Dense boolean states, packed into bits, and the planning over them.
+
+use std::collections::BTreeSet;
+use pathfinding::prelude::astar;
+use {Action, Atom, Condition, Effect, GoalAtom, State, ToGoal, Value, plan};
+
+/// The most atoms a schema can have, one per bit of a `BitState`.
+pub const MAX_SCHEMA_ATOMS: usize = 64;
+
+/// A state of boolean atoms, where each atom of a `StateSchema` is a bit.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Default, Debug)]
+pub struct BitState {
+    present: u64,
+    values: u64,
+}
+
+impl BitState {
+    /// Returns the value of the atom with the given index, if it's part of the state.
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if self.present & (1 << index) == 0 {
+            None
+        } else {
+            Some(self.values & (1 << index) != 0)
+        }
+    }
+
+    /// Sets the value of the atom with the given index.
+    pub fn set(&mut self, index: usize, value: bool) {
+        self.present |= 1 << index;
+        if value {
+            self.values |= 1 << index;
+        } else {
+            self.values &= !(1 << index);
+        }
+    }
+}
+
+/// A fixed set of atoms, giving each of them the index of its bit in a `BitState`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct StateSchema<K: Atom = String> {
+    atoms: Vec<K>,
+}
+
+impl<K: Atom> StateSchema<K> {
+    /// Makes a schema out of the given atoms, which get their indices in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than `MAX_SCHEMA_ATOMS` distinct atoms.
+    pub fn new<I: IntoIterator<Item = K>>(atoms: I) -> StateSchema<K> {
+        let atoms: Vec<K> = atoms.into_iter().collect::<BTreeSet<_>>().into_iter().collect();
+        assert!(atoms.len() <= MAX_SCHEMA_ATOMS,
+                "schemas have at most {} atoms",
+                MAX_SCHEMA_ATOMS);
+        StateSchema { atoms }
+    }
+
+    /// Makes the schema of the atoms of a domain, or returns `None` if there are too many of them.
+    pub fn for_domain<G, C>(initial_state: &State<K>,
+                            goal_state: &G,
+                            actions: &[Action<K, C>])
+                            -> Option<StateSchema<K>>
+        where G: ToGoal<K> + ?Sized
+    {
+        let mut atoms: BTreeSet<K> = initial_state.keys().cloned().collect();
+        atoms.extend(goal_state.to_goal().into_keys());
+        for action in actions {
+            atoms.extend(action.pre_conditions.keys().cloned());
+            atoms.extend(action.post_conditions.keys().cloned());
+        }
+
+        if atoms.len() > MAX_SCHEMA_ATOMS {
+            None
+        } else {
+            Some(StateSchema::new(atoms))
+        }
+    }
+
+    /// Returns the atoms of this schema, in the order of their indices.
+    pub fn atoms(&self) -> &[K] {
+        &self.atoms
+    }
+
+    /// Returns the index of an atom, if it's part of this schema.
+    pub fn index_of(&self, atom: &K) -> Option<usize> {
+        self.atoms.binary_search(atom).ok()
+    }
+
+    /// Packs a state into bits, or returns `None` if it has atoms which aren't part of this schema,
+    /// or which aren't booleans.
+    pub fn to_bitstate(&self, state: &State<K>) -> Option<BitState> {
+        let mut bits = BitState::default();
+        for (name, value) in state {
+            match *value {
+                Value::Bool(value) => bits.set(self.index_of(name)?, value),
+                _ => return None,
+            }
+        }
+
+        Some(bits)
+    }
+
+    /// Unpacks a state out of its bits.
+    pub fn from_bitstate(&self, bits: BitState) -> State<K> {
+        self.atoms
+            .iter()
+            .enumerate()
+            .filter_map(|(index, name)| bits.get(index).map(|value| (name.clone(), value.into())))
+            .collect()
+    }
+
+    /// Packs goal atoms into bits, or returns `None` if they aren't all about boolean atoms of this
+    /// schema.
+    fn to_bitgoal(&self, goal: &[(K, GoalAtom)]) -> Option<BitGoal> {
+        let mut bits = BitGoal::default();
+        for (name, goal_atom) in goal {
+            let bit = 1 << self.index_of(name)?;
+            bits.present_mask |= bit;
+            match *goal_atom {
+                GoalAtom::Required(Value::Bool(value)) => {
+                    bits.present |= bit;
+                    bits.value_mask |= bit;
+                    if value {
+                        bits.values |= bit;
+                    }
+                }
+                GoalAtom::Required(_) => return None,
+                GoalAtom::MustBeAbsent => {}
+                GoalAtom::Present => bits.present |= bit,
+            }
+        }
+
+        Some(bits)
+    }
+}
+
+/// Requirements on bit states, such as goal atoms or pre-conditions.
+#[derive(Clone, Copy, Default, Debug)]
+struct BitGoal {
+    present_mask: u64,
+    present: u64,
+    value_mask: u64,
+    values: u64,
+}
+
+impl BitGoal {
+    /// Returns the bits of the atoms whose requirements a state doesn't fulfill.
+    fn unmet(&self, state: &BitState) -> u64 {
+        ((state.present ^ self.present) & self.present_mask) |
+        ((state.values ^ self.values) & self.value_mask)
+    }
+}
+
+/// An action, packed into bits.
+struct BitAction {
+    index: usize,
+    cost: usize,
+    pre_conditions: BitGoal,
+    effect_mask: u64,
+    effect_values: u64,
+}
+
+impl BitAction {
+    /// Packs an action into bits, or returns `None` if it does more than requiring and setting
+    /// boolean atoms.
+    fn new<K: Atom>(schema: &StateSchema<K>,
+                    index: usize,
+                    action: &Action<K>)
+                    -> Option<BitAction> {
+        if !action.relational_pre_conditions.is_empty() || !action.any_of.is_empty() ||
+           action.once || action.group.is_some() || action.context_check.is_some() ||
+           action.cost_fn.is_some() {
+            return None;
+        }
+
+        let pre_conditions: Vec<(K, GoalAtom)> = action.pre_conditions
+            .iter()
+            .map(|(name, condition)| match *condition {
+                Condition::Equal(value) => Some((name.clone(), GoalAtom::Required(value))),
+                _ => None,
+            })
+            .collect::<Option<_>>()?;
+        let mut bit_action = BitAction {
+            index,
+            cost: action.cost,
+            pre_conditions: schema.to_bitgoal(&pre_conditions)?,
+            effect_mask: 0,
+            effect_values: 0,
+        };
+        for (name, effect) in &action.post_conditions {
+            let bit = 1 << schema.index_of(name)?;
+            match *effect {
+                Effect::Set(Value::Bool(value)) => {
+                    bit_action.effect_mask |= bit;
+                    if value {
+                        bit_action.effect_values |= bit;
+                    }
+                }
+                _ => return None,
+            }
+        }
+
+        Some(bit_action)
+    }
+
+    /// Returns the state this action leads to from a state, if it's applicable to it.
+    fn apply(&self, state: &BitState) -> Option<BitState> {
+        if self.pre_conditions.unmet(state) != 0 {
+            return None;
+        }
+
+        Some(BitState {
+            present: state.present | self.effect_mask,
+            values: (state.values & !self.effect_mask) | self.effect_values,
+        })
+    }
+}
+
+/// Formulates a plan like `plan` does, searching over bit states when the domain is dense enough.
+///
+/// A domain is dense when it has at most `MAX_SCHEMA_ATOMS` atoms, all of them booleans, and when
+/// its actions only require and set their values, with static costs, and neither context checks,
+/// relational pre-conditions, nor limits on how often they're applied. Checking and applying
+/// actions then only takes a few bitwise operations. Other domains are planned for with `plan`.
+pub fn plan_dense<'a, G, K>(initial_state: &'a State<K>,
+                            goal_state: &G,
+                            allowed_actions: &'a [Action<K>])
+                            -> Option<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let dense = StateSchema::for_domain(initial_state, goal_state, allowed_actions)
+        .and_then(|schema| {
+            let goal: Vec<_> = goal_state.to_goal().into_iter().collect();
+            let actions = allowed_actions.iter()
+                .enumerate()
+                .map(|(index, action)| BitAction::new(&schema, index, action))
+                .collect::<Option<Vec<_>>>()?;
+            Some((schema.to_bitstate(initial_state)?, schema.to_bitgoal(&goal)?, actions))
+        });
+    let (initial, goal, mut actions) = match dense {
+        Some(dense) => dense,
+        None => return plan(initial_state, goal_state, allowed_actions),
+    };
+
+    // Actions are tried in the same order as `plan` does, so that both find the same plan.
+    actions.sort_by(|a, b| {
+        let (a_action, b_action) = (&allowed_actions[a.index], &allowed_actions[b.index]);
+        a_action.name.cmp(&b_action.name).then(a.cost.cmp(&b.cost)).then(a.index.cmp(&b.index))
+    });
+
+    let (path, _) = astar(&initial,
+                          |state| {
+                              actions.iter()
+                                  .filter_map(|action| {
+                                      action.apply(state).map(|child| (child, action.cost))
+                                  })
+                                  .collect::<Vec<_>>()
+                          },
+                          |state| goal.unmet(state).count_ones() as usize,
+                          |state| goal.unmet(state) == 0)?;
+
+    // The cheapest action between each pair of states is the one planned, the first one among
+    // equally cheap ones, as `plan` does.
+    Some(path.windows(2)
+        .map(|pair| {
+            let action = actions.iter()
+                .filter(|action| action.apply(&pair[0]) == Some(pair[1]))
+                .min_by_key(|action| action.cost)
+                .unwrap();
+            &allowed_actions[action.index]
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generate_domain;
+
+    #[test]
+    fn test_bitstate_conversions() {
+        let mut state = State::new();
+        state.insert("door_open".to_string(), true.into());
+        state.insert("has_key".to_string(), false.into());
+
+        let schema = StateSchema::new(vec!["has_key".to_string(),
+                                           "door_open".to_string(),
+                                           "inside".to_string()]);
+        assert_eq!(schema.index_of(&"door_open".to_string()), Some(0));
+        let bits = schema.to_bitstate(&state).unwrap();
+        assert_eq!((bits.get(0), bits.get(1), bits.get(2)), (Some(true), Some(false), None));
+        assert_eq!(schema.from_bitstate(bits), state);
+
+        // Only the boolean atoms of the schema fit in bits.
+        state.insert("wood".to_string(), 3.into());
+        assert_eq!(schema.to_bitstate(&state), None);
+        let schema = StateSchema::new(state.keys().cloned());
+        assert_eq!(schema.to_bitstate(&state), None);
+    }
+
+    #[test]
+    fn test_plan_dense() {
+        // Dense domains are planned for over bits, and lead to the same plans as the bigger maps.
+        for seed in 0..20 {
+            for &(num_atoms, num_actions) in &[(8, 20), (16, 50), (64, 200)] {
+                let (initial_state, goal_state, actions) =
+                    generate_domain(num_atoms, num_actions, seed);
+                let schema = StateSchema::for_domain(&initial_state, &goal_state, &actions)
+                    .unwrap();
+                assert!(actions.iter().all(|action| BitAction::new(&schema, 0, action).is_some()));
+                assert_eq!(plan_dense(&initial_state, &goal_state, &actions),
+                           plan(&initial_state, &goal_state, &actions));
+            }
+        }
+
+        // Other domains are planned for with the maps.
+        let actions = [Action::new("chop_wood", 1).produces("wood", Effect::Add(1)),
+                       Action::new("build_house", 1)
+                           .requires("wood", Condition::GreaterOrEqual(2))
+                           .produces("has_house", true)];
+        let mut initial_state = State::new();
+        initial_state.insert("wood".to_string(), 0.into());
+        let mut goal_state = State::new();
+        goal_state.insert("has_house".to_string(), true.into());
+        assert_eq!(plan_dense(&initial_state, &goal_state, &actions).unwrap().len(), 3);
+
+        // Among equally cheap actions leading to the same state, the first one by name is planned,
+        // as `plan` does, whatever the order of the actions.
+        let mut actions = vec![Action::new("walk_to_door", 1).produces("at_door", true),
+                               Action::new("stroll_to_door", 1).produces("at_door", true)];
+        let mut initial_state = State::new();
+        initial_state.insert("at_door".to_string(), false.into());
+        let mut goal_state = State::new();
+        goal_state.insert("at_door".to_string(), true.into());
+        for _ in 0..2 {
+            let planned = plan_dense(&initial_state, &goal_state, &actions).unwrap();
+            assert_eq!(planned[0].name, "stroll_to_door");
+            actions.reverse();
+        }
+    }
+}
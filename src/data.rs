@@ -0,0 +1,99 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Domains described in data files, with the `use_serde` feature.
+
+extern crate serde_json;
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use {Action, State, plan};
+
+/// A planning problem: the actions to plan with, the state to start from, and the goal state.
+///
+/// Domains deserialize from objects with `actions`, `initial_state` and `goal_state` fields, in
+/// any format serde supports. `from_reader` and `from_path` read them from JSON.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Domain {
+    /// The actions to plan with.
+    pub actions: Vec<Action>,
+    /// The state to start from.
+    pub initial_state: State,
+    /// The state to get to.
+    pub goal_state: State,
+}
+
+/// The reasons why a domain couldn't be loaded.
+#[derive(Debug)]
+pub enum DomainError {
+    /// The file couldn't be read.
+    Io(io::Error),
+    /// The file isn't the JSON of a domain.
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for DomainError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DomainError::Io(ref error) => write!(f, "the domain couldn't be read: {}", error),
+            DomainError::Parse(ref error) => write!(f, "the domain is malformed: {}", error),
+        }
+    }
+}
+
+impl Error for DomainError {}
+
+impl Domain {
+    /// Reads a domain from JSON.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Domain, DomainError> {
+        serde_json::from_reader(reader).map_err(DomainError::Parse)
+    }
+
+    /// Reads a domain from a JSON file.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Domain, DomainError> {
+        let file = fs::File::open(path).map_err(DomainError::Io)?;
+        Domain::from_reader(io::BufReader::new(file))
+    }
+
+    /// Formulates a plan from the initial state to the goal state of this domain, as `plan` does.
+    pub fn plan(&self) -> Option<Vec<&Action>> {
+        plan(&self.initial_state, &self.goal_state, &self.actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Condition, Effect};
+
+    #[test]
+    fn test_domain_round_trip() {
+        let mut initial_state = State::new();
+        initial_state.insert("wood".to_string(), 0.into());
+        let mut goal_state = State::new();
+        goal_state.insert("has_house".to_string(), true.into());
+        let domain = Domain {
+            actions: vec![Action::new("chop_wood", 1).produces("wood", Effect::Add(1)),
+                          Action::new("build_house", 2)
+                              .requires("wood", Condition::GreaterOrEqual(2))
+                              .produces("has_house", true)],
+            initial_state,
+            goal_state,
+        };
+
+        let json = serde_json::to_string(&domain).unwrap();
+        let loaded = Domain::from_reader(json.as_bytes()).unwrap();
+        assert_eq!(loaded, domain);
+        let names: Vec<&str> = loaded.plan().unwrap().iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["chop_wood", "chop_wood", "build_house"]);
+
+        // The test cases are domains too, along with their expectations.
+        let domain = Domain::from_path("data/collect_wood.json").unwrap();
+        assert!(domain.plan().is_some());
+        assert!(matches!(Domain::from_path("data/missing.json"), Err(DomainError::Io(_))));
+        assert!(matches!(Domain::from_reader(&b"{\"actions\": []}"[..]),
+                         Err(DomainError::Parse(_))));
+    }
+}
@@ -0,0 +1,709 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! The analysis of planning domains, to help authoring them.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+#[cfg(not(feature = "std"))]
+use prelude::*;
+use {Action, Atom, Condition, Effect, GoalAtom, RelationalPre, State, ToGoal, Value};
+
+/// A problem found in a planning domain, which makes some plans impossible.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum DomainWarning<K = String> {
+    /// The named action requires an atom which is neither in the initial state nor in any
+    /// action's post-conditions, so the action can never be applied.
+    ConditionNeverMet(String, K),
+    /// A goal atom is neither in the initial state nor in any action's post-conditions.
+    GoalAtomNeverProduced(K),
+    /// Several actions have this name, so plans can't tell them apart by name.
+    DuplicateActionName(String),
+    /// The atoms the named action requires are all produced, but never with values fulfilling its
+    /// pre-conditions, so the action can never be applied.
+    DeadAction(String),
+}
+
+impl<K: fmt::Display> fmt::Display for DomainWarning<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DomainWarning::ConditionNeverMet(ref name, ref atom) => {
+                write!(f, "the action `{}` requires `{}`, which is never produced", name, atom)
+            }
+            DomainWarning::GoalAtomNeverProduced(ref atom) => {
+                write!(f, "the goal atom `{}` is never produced", atom)
+            }
+            DomainWarning::DuplicateActionName(ref name) => {
+                write!(f, "several actions are named `{}`", name)
+            }
+            DomainWarning::DeadAction(ref name) => {
+                write!(f, "the action `{}` can never be applied", name)
+            }
+        }
+    }
+}
+
+/// Whether an action is applicable to a state, as reported by `applicability_report`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Applicable<K = String> {
+    /// All the pre-conditions of the action are fulfilled.
+    Yes,
+    /// The pre-conditions of the action on these atoms aren't fulfilled.
+    No { unmet: Vec<K> },
+}
+
+/// Tells which of a set of actions are applicable to a state, and for those which aren't, which of
+/// their pre-conditions aren't fulfilled, along with the name of each action.
+pub fn applicability_report<K: Atom>(state: &State<K>,
+                                     actions: &[Action<K>])
+                                     -> Vec<(String, Applicable<K>)> {
+    actions.iter()
+        .map(|action| {
+            let mut unmet: Vec<K> = action.pre_conditions
+                .iter()
+                .filter(|&(name, condition)| !condition.is_met_by(state.get(name)))
+                .map(|(name, _)| name.clone())
+                .collect();
+            for pre in &action.relational_pre_conditions {
+                if !pre.is_met_by(state.get(&pre.left), state.get(&pre.right)) {
+                    unmet.push(pre.left.clone());
+                    unmet.push(pre.right.clone());
+                }
+            }
+            let unmet_alternatives: Vec<Vec<&K>> = action.any_of
+                .iter()
+                .map(|alternative| {
+                    alternative.iter()
+                        .filter(|&(name, value)| state.get(name) != Some(value))
+                        .map(|(name, _)| name)
+                        .collect()
+                })
+                .collect();
+            if unmet_alternatives.iter().all(|unmet| !unmet.is_empty()) {
+                unmet.extend(unmet_alternatives.into_iter().flatten().cloned());
+            }
+            let applicable = if unmet.is_empty() {
+                Applicable::Yes
+            } else {
+                Applicable::No { unmet }
+            };
+            (action.name.clone(), applicable)
+        })
+        .collect()
+}
+
+/// Looks for atoms which are required, either by the pre-conditions of an action or by the goal,
+/// but which are neither in the initial state nor in any action's post-conditions, for actions
+/// which can never be applied, and for names shared by several actions.
+///
+/// Atoms which are never produced never get a value, so the actions requiring them can never be
+/// applied, and the goal requiring them can never be reached. Other actions are dead when no
+/// value their atoms can be given, by the initial state or by the effects of the actions which
+/// aren't dead themselves, fulfills their pre-conditions. Each pre-condition is considered on its
+/// own, so some dead actions may go unnoticed, but an action reported as dead is never applicable.
+///
+/// The warnings come in the order of the actions, followed by the goal atoms. Duplicate names are
+/// reported once each, where they first appear again, and actions requiring atoms which are never
+/// produced aren't reported as dead on top of it.
+pub fn check_domain<G, K>(initial_state: &State<K>,
+                          goal_state: &G,
+                          allowed_actions: &[Action<K>])
+                          -> Vec<DomainWarning<K>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let is_produced = |name: &K| {
+        initial_state.contains_key(name) ||
+        allowed_actions.iter().any(|action| action.post_conditions.contains_key(name))
+    };
+
+    let (_, is_live) = relax_domain(initial_state, allowed_actions);
+    let mut warnings = vec![];
+    for (index, action) in allowed_actions.iter().enumerate() {
+        let previous = &allowed_actions[..index];
+        if previous.iter().filter(|other| other.name == action.name).count() == 1 {
+            warnings.push(DomainWarning::DuplicateActionName(action.name.clone()));
+        }
+        let relational_names = action.relational_pre_conditions
+            .iter()
+            .flat_map(|pre| vec![&pre.left, &pre.right]);
+        let warning_count = warnings.len();
+        for name in action.pre_conditions.keys().chain(relational_names) {
+            if !is_produced(name) {
+                warnings.push(DomainWarning::ConditionNeverMet(action.name.clone(), name.clone()));
+            }
+        }
+        if warnings.len() == warning_count && !is_live[index] {
+            warnings.push(DomainWarning::DeadAction(action.name.clone()));
+        }
+    }
+    for (name, goal_atom) in &goal_state.to_goal() {
+        if let GoalAtom::MustBeAbsent = *goal_atom {
+            continue;
+        }
+        if !is_produced(name) {
+            warnings.push(DomainWarning::GoalAtomNeverProduced(name.clone()));
+        }
+    }
+
+    warnings
+}
+
+/// The values an atom can be given, as far as a domain tells.
+#[derive(Default)]
+struct PossibleValues {
+    values: BTreeSet<Value>,
+    /// Whether relative effects can give the atom any integer value, however many.
+    any_int: bool,
+}
+
+impl PossibleValues {
+    /// Returns the number of possible values, which is only said to be 2 when there are more.
+    fn count(&self) -> usize {
+        if self.any_int {
+            2
+        } else {
+            self.values.len().min(2)
+        }
+    }
+
+    /// Returns `true` if one of the possible values fulfills a condition.
+    fn may_meet(&self, condition: &Condition) -> bool {
+        let any_int_meets = match *condition {
+            Condition::Equal(Value::Int(_)) => true,
+            Condition::Equal(_) => false,
+            _ => true,
+        };
+        (self.any_int && any_int_meets) ||
+        self.values.iter().any(|value| condition.is_met_by(Some(value)))
+    }
+
+    /// Returns `true` if one of the possible values of this atom and one of the possible values of
+    /// another atom can be the same.
+    fn may_equal(&self, other: &PossibleValues) -> bool {
+        let has_int = |possible: &PossibleValues| {
+            possible.any_int || possible.values.iter().any(|value| matches!(*value, Value::Int(_)))
+        };
+        (self.any_int && has_int(other)) || (other.any_int && has_int(self)) ||
+        self.values.intersection(&other.values).next().is_some()
+    }
+
+    /// Adds the values an effect can give the atom.
+    fn add(&mut self, effect: &Effect) {
+        match *effect {
+            Effect::Set(value) => {
+                self.values.insert(value);
+            }
+            Effect::Add(_) => self.any_int = true,
+            // Toggles can give either boolean value, depending on the current one.
+            Effect::Toggle => {
+                self.values.insert(Value::Bool(false));
+                self.values.insert(Value::Bool(true));
+            }
+        }
+    }
+}
+
+/// Tells which actions may be applicable at some point, by gathering the values each atom can be
+/// given by the initial state and by the actions which may be applicable, until no more action is.
+///
+/// Returns the values each atom can be given, along with whether each action may be applicable.
+fn relax_domain<'a, K: Atom>(initial_state: &'a State<K>,
+                             actions: &'a [Action<K>])
+                             -> (BTreeMap<&'a K, PossibleValues>, Vec<bool>) {
+    let mut possible: BTreeMap<&K, PossibleValues> = BTreeMap::new();
+    for (name, value) in initial_state {
+        possible.entry(name).or_default().values.insert(*value);
+    }
+
+    let mut is_live = vec![false; actions.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (index, action) in actions.iter().enumerate() {
+            if is_live[index] || !may_apply(&possible, action) {
+                continue;
+            }
+
+            is_live[index] = true;
+            changed = true;
+            for (name, effect) in &action.post_conditions {
+                possible.entry(name).or_default().add(effect);
+            }
+        }
+    }
+
+    (possible, is_live)
+}
+
+/// Returns `true` if each pre-condition of an action can be fulfilled with the possible values of
+/// its atoms.
+fn may_apply<K: Atom>(possible: &BTreeMap<&K, PossibleValues>, action: &Action<K>) -> bool {
+    action.pre_conditions.iter().all(|(name, condition)| may_meet(possible, name, condition)) &&
+    action.relational_pre_conditions.iter().all(|pre| may_meet_relational(possible, pre)) &&
+    (action.any_of.is_empty() ||
+     action.any_of.iter().any(|alternative| may_meet_alternative(possible, alternative)))
+}
+
+/// Returns `true` if each atom of an alternative of an action can have its value.
+fn may_meet_alternative<K: Atom>(possible: &BTreeMap<&K, PossibleValues>,
+                                 alternative: &State<K>)
+                                 -> bool {
+    alternative.iter().all(|(name, value)| may_meet(possible, name, &Condition::Equal(*value)))
+}
+
+/// Returns `true` if one of the possible values of an atom fulfills a condition.
+fn may_meet<K: Atom>(possible: &BTreeMap<&K, PossibleValues>,
+                     name: &K,
+                     condition: &Condition)
+                     -> bool {
+    possible.get(name).is_some_and(|possible| possible.may_meet(condition))
+}
+
+/// Returns `true` if some possible values of two atoms fulfill a relational pre-condition.
+fn may_meet_relational<K: Atom>(possible: &BTreeMap<&K, PossibleValues>,
+                                pre: &RelationalPre<K>)
+                                -> bool {
+    match (possible.get(&pre.left), possible.get(&pre.right)) {
+        (Some(left), Some(right)) if pre.equal => left.may_equal(right),
+        (Some(left), Some(right)) => {
+            left.count() > 1 || right.count() > 1 || left.values != right.values
+        }
+        _ => false,
+    }
+}
+
+/// Describes what a condition requires of an atom, as in "be at least 3".
+fn describe_condition(condition: &Condition) -> String {
+    match *condition {
+        Condition::Equal(value) => format!("be {:?}", value),
+        Condition::GreaterOrEqual(bound) => format!("be at least {}", bound),
+        Condition::Greater(bound) => format!("be more than {}", bound),
+        Condition::LessOrEqual(bound) => format!("be at most {}", bound),
+        Condition::Less(bound) => format!("be less than {}", bound),
+    }
+}
+
+/// The explanations of why some requirements can never be fulfilled, as they're being gathered.
+struct FailureExplainer<'a, K: 'a + Atom> {
+    actions: &'a [Action<K>],
+    possible: BTreeMap<&'a K, PossibleValues>,
+    /// Whether each action was already explained, so that each is explained once.
+    explained: Vec<bool>,
+    explanations: Vec<String>,
+}
+
+impl<'a, K: Atom + fmt::Display> FailureExplainer<'a, K> {
+    /// Explains why an atom can never fulfill a condition, if it can't, and why the actions which
+    /// could make it fulfill the condition can never be applied.
+    fn explain_condition(&mut self, requirer: &str, name: &K, condition: &Condition) {
+        if may_meet(&self.possible, name, condition) {
+            return;
+        }
+
+        let producers: Vec<usize> = self.actions
+            .iter()
+            .enumerate()
+            .filter(|&(_, action)| {
+                action.post_conditions.get(name).is_some_and(|effect| {
+                    let mut produced = PossibleValues::default();
+                    produced.add(effect);
+                    produced.may_meet(condition)
+                })
+            })
+            .map(|(index, _)| index)
+            .collect();
+        let requirement =
+            format!("{} requires `{}` to {}", requirer, name, describe_condition(condition));
+        if producers.is_empty() {
+            self.explanations.push(format!("{}, but no action does so", requirement));
+            return;
+        }
+
+        let names: Vec<String> = producers.iter()
+            .map(|&index| format!("`{}`", self.actions[index].name))
+            .collect();
+        self.explanations.push(format!("{}, but the only actions doing so, {}, can never be \
+                                        applied",
+                                       requirement,
+                                       names.join(", ")));
+        for index in producers {
+            self.explain_action(index);
+        }
+    }
+
+    /// Explains why an action which can never be applied can't, unless it was already explained.
+    fn explain_action(&mut self, index: usize) {
+        if self.explained[index] {
+            return;
+        }
+        self.explained[index] = true;
+
+        let action = &self.actions[index];
+        let requirer = format!("the action `{}`", action.name);
+        for (name, condition) in &action.pre_conditions {
+            self.explain_condition(&requirer, name, condition);
+        }
+        let possible = &self.possible;
+        let may_meet_any =
+            action.any_of.iter().any(|alternative| may_meet_alternative(possible, alternative));
+        if !action.any_of.is_empty() && !may_meet_any {
+            for alternative in &action.any_of {
+                for (name, value) in alternative {
+                    self.explain_condition(&requirer, name, &Condition::Equal(*value));
+                }
+            }
+        }
+        for pre in &action.relational_pre_conditions {
+            if !may_meet_relational(&self.possible, pre) {
+                let relation = if pre.equal { "equal" } else { "different" };
+                self.explanations.push(format!("{} requires `{}` and `{}` to be {}, which they \
+                                                can never be",
+                                               requirer,
+                                               pre.left,
+                                               pre.right,
+                                               relation));
+            }
+        }
+    }
+}
+
+/// Explains why the goal atoms which can never be fulfilled can't, such as when no action
+/// produces an atom they require, or when the only actions producing it require atoms which are
+/// never produced themselves.
+///
+/// Effects are assumed not to overwrite the values atoms had before, as FF-style planners do with
+/// delete effects when relaxing a problem, so an explanation is only given when there's no plan at
+/// all, but a plan may be missing even when there's no explanation. Each explanation tells what a
+/// requirement is, followed by the explanations about the actions which could have fulfilled it,
+/// in order. Each action is explained once, the first time it's needed.
+pub fn explain_failure<G, K>(initial_state: &State<K>,
+                             goal_state: &G,
+                             allowed_actions: &[Action<K>])
+                             -> Vec<String>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom + fmt::Display
+{
+    let (possible, _) = relax_domain(initial_state, allowed_actions);
+    let mut explainer = FailureExplainer {
+        actions: allowed_actions,
+        possible,
+        explained: vec![false; allowed_actions.len()],
+        explanations: vec![],
+    };
+    for (name, goal_atom) in &goal_state.to_goal() {
+        match *goal_atom {
+            GoalAtom::Required(value) => {
+                explainer.explain_condition("the goal", name, &Condition::Equal(value))
+            }
+            GoalAtom::MustBeAbsent if initial_state.contains_key(name) => {
+                explainer.explanations.push(format!("the goal requires `{}` to be absent, but \
+                                                     it's part of the initial state",
+                                                    name))
+            }
+            GoalAtom::Present if !explainer.possible.contains_key(name) => {
+                explainer.explanations
+                    .push(format!("the goal requires `{}` to be present, but no action does so",
+                                  name))
+            }
+            _ => {}
+        }
+    }
+
+    explainer.explanations
+}
+
+/// Returns the actions which may be part of a plan to get from an initial state to a goal state,
+/// in the order they're given in, so that the others can be left out of the set of actions.
+///
+/// These are the actions which may be applicable at some point, as told by `check_domain`, and
+/// which produce values the goal requires, or which the pre-conditions of other such actions
+/// require. Leaving the others out changes no plan, but some of the actions returned may never be
+/// planned either, such as the more expensive ways to do the same thing.
+pub fn used_actions_closure<'a, G, K>(initial_state: &State<K>,
+                                      goal_state: &G,
+                                      allowed_actions: &'a [Action<K>])
+                                      -> Vec<&'a Action<K>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let (_, is_live) = relax_domain(initial_state, allowed_actions);
+    let (is_used, _) = backward_relevance(goal_state, allowed_actions, &is_live);
+
+    allowed_actions.iter()
+        .zip(is_used)
+        .filter(|&(_, is_used)| is_used)
+        .map(|(action, _)| action)
+        .collect()
+}
+
+/// Returns `true` if an action produces values a goal requires, or which the pre-conditions of
+/// other actions producing such values require, and so on, so that it may help reach the goal.
+///
+/// Unlike `used_actions_closure`, this doesn't tell whether the actions can ever be applied, so an
+/// action which is relevant to a goal may still never be planned.
+pub fn is_relevant<G, K>(action: &Action<K>, goal_state: &G, allowed_actions: &[Action<K>]) -> bool
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let is_live = vec![true; allowed_actions.len()];
+    let (_, requirements) = backward_relevance(goal_state, allowed_actions, &is_live);
+    requirements.iter().any(|(name, condition)| helps(action, name, condition.as_ref()))
+}
+
+/// Finds the live actions which produce values a goal requires, or which the pre-conditions of
+/// other such actions require, until no more are found. Returns which actions those are, along
+/// with all the requirements found, where `None` stands for any value.
+fn backward_relevance<G, K>(goal_state: &G,
+                            allowed_actions: &[Action<K>],
+                            is_live: &[bool])
+                            -> (Vec<bool>, Vec<(K, Option<Condition>)>)
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut requirements: Vec<(K, Option<Condition>)> = goal_state.to_goal()
+        .into_iter()
+        .filter_map(|(name, goal_atom)| match goal_atom {
+            GoalAtom::Required(value) => Some((name, Some(Condition::Equal(value)))),
+            GoalAtom::Present => Some((name, None)),
+            GoalAtom::MustBeAbsent => None,
+        })
+        .collect();
+    let mut is_used = vec![false; allowed_actions.len()];
+    let mut next = 0;
+    while next < requirements.len() {
+        let (name, condition) = requirements[next].clone();
+        next += 1;
+        for (index, action) in allowed_actions.iter().enumerate() {
+            if is_used[index] || !is_live[index] || !helps(action, &name, condition.as_ref()) {
+                continue;
+            }
+
+            is_used[index] = true;
+            for (name, condition) in &action.pre_conditions {
+                requirements.push((name.clone(), Some(*condition)));
+            }
+            for pre in &action.relational_pre_conditions {
+                requirements.push((pre.left.clone(), None));
+                requirements.push((pre.right.clone(), None));
+            }
+            for (name, value) in action.any_of.iter().flat_map(|alternative| alternative.iter()) {
+                requirements.push((name.clone(), Some(Condition::Equal(*value))));
+            }
+        }
+    }
+
+    (is_used, requirements)
+}
+
+/// Returns `true` if an action may produce a value of an atom meeting a condition, or any value
+/// when there's no condition.
+fn helps<K: Atom>(action: &Action<K>, name: &K, condition: Option<&Condition>) -> bool {
+    action.post_conditions.get(name).is_some_and(|effect| {
+        let mut produced = PossibleValues::default();
+        produced.add(effect);
+        condition.is_none_or(|condition| produced.may_meet(condition))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Goal, state};
+
+    #[test]
+    fn test_check_domain() {
+        let actions = [Action::new("get_key", 1).produces("has_key", true),
+                       Action::new("open_door", 1)
+                           .requires("has_key", true)
+                           .produces("door_open", true),
+                       Action::new("pick_lock", 1)
+                           .requires("has_lockpick", true)
+                           .produces("door_open", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("door_open".to_string(), false.into());
+
+        let mut goal = Goal::new();
+        goal.insert("door_open".to_string(), true.into());
+        goal.insert("inside".to_string(), true.into());
+        goal.insert("alarm_on".to_string(), GoalAtom::MustBeAbsent);
+
+        let warnings = check_domain(&initial_state, &goal, &actions);
+        assert_eq!(warnings,
+                   vec![DomainWarning::ConditionNeverMet("pick_lock".to_string(),
+                                                         "has_lockpick".to_string()),
+                        DomainWarning::GoalAtomNeverProduced("inside".to_string())]);
+        assert_eq!(warnings[0].to_string(),
+                   "the action `pick_lock` requires `has_lockpick`, which is never produced");
+
+        // Atoms in the initial state are there from the start, but not always with the values the
+        // actions require.
+        initial_state.insert("has_lockpick".to_string(), false.into());
+        initial_state.insert("inside".to_string(), false.into());
+        let warnings = check_domain(&initial_state, &goal, &actions);
+        assert_eq!(warnings, vec![DomainWarning::DeadAction("pick_lock".to_string())]);
+        assert_eq!(warnings[0].to_string(), "the action `pick_lock` can never be applied");
+        initial_state.insert("has_lockpick".to_string(), true.into());
+        assert_eq!(check_domain(&initial_state, &goal, &actions), vec![]);
+
+        // Values are produced by the actions which can be applied, and added to without bounds.
+        let actions = [Action::new("chop_wood", 1).produces("wood", Effect::Add(1)),
+                       Action::new("build_house", 1)
+                           .requires("wood", Condition::GreaterOrEqual(10))
+                           .produces("has_house", true),
+                       Action::new("sell_house", 1)
+                           .requires("has_house", true)
+                           .produces("door_open", true),
+                       Action::new("buy_lockpick", 1)
+                           .requires("gold", Condition::Greater(0))
+                           .produces("has_lockpick", true),
+                       Action::new("pick_lock", 1)
+                           .requires("has_lockpick", true)
+                           .produces("door_open", true)];
+        initial_state.insert("has_lockpick".to_string(), false.into());
+        initial_state.insert("gold".to_string(), 0.into());
+        assert_eq!(check_domain(&initial_state, &goal, &actions),
+                   vec![DomainWarning::DeadAction("buy_lockpick".to_string()),
+                        DomainWarning::DeadAction("pick_lock".to_string())]);
+
+        // Adding to an atom only ever makes integers, never enumerated values.
+        let actions = [Action::new("chop_wood", 1).produces("wood", Effect::Add(1)),
+                       Action::new("burn_wood", 1)
+                           .requires("wood", Condition::Equal(Value::Enum(1)))
+                           .produces("door_open", true)];
+        initial_state.insert("wood".to_string(), 0.into());
+        assert_eq!(check_domain(&initial_state, &goal, &actions),
+                   vec![DomainWarning::DeadAction("burn_wood".to_string())]);
+
+        let actions = [Action::new("open", 1).produces("door_open", true),
+                       Action::new("open", 2).produces("door_open", true),
+                       Action::new("open", 3).produces("door_open", true)];
+        assert_eq!(check_domain(&initial_state, &goal, &actions),
+                   vec![DomainWarning::DuplicateActionName("open".to_string())]);
+    }
+
+    #[test]
+    fn test_explain_failure() {
+        let actions = [Action::new("go_to_shop", 1)
+                           .requires("has_map", true)
+                           .produces("at_shop", true),
+                       Action::new("buy_key", 1)
+                           .requires("at_shop", true)
+                           .requires("gold", Condition::GreaterOrEqual(5))
+                           .produces("has_key", true),
+                       Action::new("steal_key", 1)
+                           .requires("at_shop", true)
+                           .produces("has_key", true),
+                       Action::new("open_door", 1)
+                           .requires("has_key", true)
+                           .produces("door_open", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("door_open".to_string(), false.into());
+        initial_state.insert("gold".to_string(), 10.into());
+
+        let mut goal = Goal::new();
+        goal.insert("door_open".to_string(), true.into());
+        goal.insert("gold".to_string(), GoalAtom::MustBeAbsent);
+        assert_eq!(explain_failure(&initial_state, &goal, &actions),
+                   vec!["the goal requires `door_open` to be Bool(true), but the only actions \
+                         doing so, `open_door`, can never be applied",
+                        "the action `open_door` requires `has_key` to be Bool(true), but the \
+                         only actions doing so, `buy_key`, `steal_key`, can never be applied",
+                        "the action `buy_key` requires `at_shop` to be Bool(true), but the only \
+                         actions doing so, `go_to_shop`, can never be applied",
+                        "the action `go_to_shop` requires `has_map` to be Bool(true), but no \
+                         action does so",
+                        "the action `steal_key` requires `at_shop` to be Bool(true), but the \
+                         only actions doing so, `go_to_shop`, can never be applied",
+                        "the goal requires `gold` to be absent, but it's part of the initial \
+                         state"]);
+
+        // Nothing to explain when the goal can be reached.
+        initial_state.insert("has_map".to_string(), true.into());
+        goal.remove("gold");
+        assert_eq!(explain_failure(&initial_state, &goal, &actions), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_used_actions_closure() {
+        let actions = [Action::new("dance", 1).produces("happy", true),
+                       Action::new("get_key", 1).produces("has_key", true),
+                       Action::new("open_door", 1)
+                           .requires("has_key", true)
+                           .produces("door_open", true),
+                       Action::new("close_door", 1).produces("door_open", false),
+                       Action::new("pick_lock", 1)
+                           .requires("has_lockpick", true)
+                           .produces("door_open", true),
+                       Action::new("chop_wood", 1).produces("wood", Effect::Add(1)),
+                       Action::new("build_ram", 1)
+                           .requires("wood", Condition::GreaterOrEqual(3))
+                           .produces("has_ram", true),
+                       Action::new("ram_door", 1)
+                           .requires("has_ram", true)
+                           .produces("door_open", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("door_open".to_string(), false.into());
+        initial_state.insert("has_lockpick".to_string(), false.into());
+
+        // Dancing doesn't help, closing the door doesn't open it, and the lock can't be picked.
+        let mut goal_state = State::new();
+        goal_state.insert("door_open".to_string(), true.into());
+        let names: Vec<&str> = used_actions_closure(&initial_state, &goal_state, &actions)
+            .iter()
+            .map(|action| action.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["get_key", "open_door", "chop_wood", "build_ram", "ram_door"]);
+
+        let mut goal = Goal::new();
+        goal.insert("happy".to_string(), GoalAtom::Present);
+        goal.insert("door_open".to_string(), GoalAtom::MustBeAbsent);
+        assert_eq!(used_actions_closure(&initial_state, &goal, &actions), vec![&actions[0]]);
+    }
+
+    #[test]
+    fn test_is_relevant() {
+        let actions = [Action::new("find_key", 1).produces("has_key", true),
+                       Action::new("open_door", 1)
+                           .requires("has_key", true)
+                           .produces("door_open", true),
+                       Action::new("drop_key", 1).produces("has_key", false),
+                       Action::new("dance", 1).produces("happy", true)];
+        let goal_state = state([("door_open", true)]);
+
+        // Opening the door helps directly, finding the key helps opening the door.
+        let relevant: Vec<bool> =
+            actions.iter().map(|action| is_relevant(action, &goal_state, &actions)).collect();
+        assert_eq!(relevant, vec![true, true, false, false]);
+
+        // Actions which aren't part of the set can still be relevant.
+        let smash_door = Action::new("smash_door", 5).produces("door_open", true);
+        assert!(is_relevant(&smash_door, &goal_state, &[]));
+    }
+
+    #[test]
+    fn test_applicability_report() {
+        let actions = [Action::new("get_key", 1).produces("has_key", true),
+                       Action::new("open_door", 1)
+                           .requires("has_key", true)
+                           .requires("at_door", true)
+                           .produces("door_open", true),
+                       Action::new("chop_tree", 1)
+                           .requires("at_door", true)
+                           .requires("wood", Condition::Less(3))
+                           .produces("wood", 3)];
+
+        let mut state = State::new();
+        state.insert("at_door".to_string(), true.into());
+        state.insert("has_key".to_string(), false.into());
+
+        assert_eq!(applicability_report(&state, &actions),
+                   vec![("get_key".to_string(), Applicable::Yes),
+                        ("open_door".to_string(),
+                         Applicable::No { unmet: vec!["has_key".to_string()] }),
+                        ("chop_tree".to_string(),
+                         Applicable::No { unmet: vec!["wood".to_string()] })]);
+    }
+}
@@ -0,0 +1,151 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! The export of the planning graph to the Graphviz DOT format.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Write};
+use std::ptr;
+use search::{SearchSpace, path_actions};
+use {Action, Atom, State, ToGoal, Value};
+
+/// Renders the graph of the states reachable from an initial state using a set of allowed actions
+/// in the Graphviz DOT format, with the plan that `plan` would formulate highlighted in red.
+///
+/// Nodes are labeled with the atoms differing from the initial state, and the nodes reaching the
+/// goal state are circled twice. Edges are labeled with the name and cost of their action. At most
+/// `max_nodes` states are rendered, the closest ones to the initial state, in number of actions.
+pub fn to_dot<G, K>(initial_state: &State<K>,
+                    goal_state: &G,
+                    allowed_actions: &[Action<K>],
+                    max_nodes: usize)
+                    -> String
+    where G: ToGoal<K> + ?Sized,
+          K: Atom + Display
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    let (plan_nodes, plan_actions) =
+        match space.search(|node| node.mismatch_count(&goal), |node| node.matches(&goal)) {
+            Some((path, _)) => {
+                let plan_actions = path_actions(&path, allowed_actions);
+                (path, plan_actions)
+            }
+            None => (vec![], vec![]),
+        };
+
+    // Nodes are numbered in the order a breadth-first walk of the graph discovers them.
+    let mut ids = HashMap::new();
+    let mut nodes = vec![];
+    let mut queue = VecDeque::new();
+    if max_nodes > 0 {
+        let initial = space.initial_node();
+        ids.insert(initial.clone(), 0);
+        nodes.push(initial.clone());
+        queue.push_back(initial);
+    }
+
+    let mut edges = vec![];
+    while let Some(node) = queue.pop_front() {
+        for (index, child, cost) in node.transitions() {
+            if !ids.contains_key(&child) {
+                if nodes.len() == max_nodes {
+                    continue;
+                }
+                ids.insert(child.clone(), nodes.len());
+                nodes.push(child.clone());
+                queue.push_back(child.clone());
+            }
+
+            let action = &allowed_actions[index];
+            let on_plan = plan_nodes.windows(2)
+                .zip(&plan_actions)
+                .any(|(pair, &planned)| {
+                    pair[0] == node && pair[1] == child && ptr::eq(planned, action)
+                });
+            edges.push((ids[&node], ids[&child], action, cost, on_plan));
+        }
+    }
+
+    let mut dot = String::from("digraph plan {\n");
+    for (id, node) in nodes.iter().enumerate() {
+        let changes: Vec<String> = node.changed_atoms()
+            .iter()
+            .map(|&(name, value)| format!("{} = {}", escape(&name.to_string()), value_label(value)))
+            .collect();
+        let label = if changes.is_empty() {
+            "initial".to_string()
+        } else {
+            changes.join("\\n")
+        };
+
+        let mut attributes = format!("label=\"{}\"", label);
+        if node.matches(&goal) {
+            attributes.push_str(", peripheries=2");
+        }
+        if plan_nodes.contains(node) {
+            attributes.push_str(", color=red");
+        }
+        writeln!(dot, "    {} [{}];", id, attributes).unwrap();
+    }
+    for (from, to, action, cost, on_plan) in edges {
+        let mut attributes = format!("label=\"{} ({})\"", escape(&action.name), cost);
+        if on_plan {
+            attributes.push_str(", color=red");
+        }
+        writeln!(dot, "    {} -> {} [{}];", from, to, attributes).unwrap();
+    }
+    dot.push_str("}\n");
+
+    dot
+}
+
+/// Escapes a string so that it can be put in between double quotes.
+fn escape(string: &str) -> String {
+    string.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Returns the text representing an atom value in labels.
+fn value_label(value: Value) -> String {
+    match value {
+        Value::Bool(value) => value.to_string(),
+        Value::Int(value) => value.to_string(),
+        Value::Enum(value) => format!("enum {}", value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tests::key_and_door;
+
+    #[test]
+    fn test_to_dot() {
+        let (initial_state, goal_state, actions) = key_and_door();
+        let dot = to_dot(&initial_state, &goal_state, &actions, 10);
+        let expected = "digraph plan {
+    0 [label=\"initial\", color=red];
+    1 [label=\"has_key = true\", color=red];
+    2 [label=\"door_open = true\", peripheries=2];
+    3 [label=\"door_open = true\\nhas_key = true\", peripheries=2, color=red];
+    0 -> 1 [label=\"get_key (1)\", color=red];
+    0 -> 2 [label=\"smash_door (5)\"];
+    1 -> 1 [label=\"get_key (1)\"];
+    1 -> 3 [label=\"open_door (1)\", color=red];
+    1 -> 3 [label=\"smash_door (5)\"];
+    2 -> 3 [label=\"get_key (1)\"];
+    2 -> 2 [label=\"smash_door (5)\"];
+    3 -> 3 [label=\"get_key (1)\"];
+    3 -> 3 [label=\"open_door (1)\"];
+    3 -> 3 [label=\"smash_door (5)\"];
+}
+";
+        assert_eq!(dot, expected);
+
+        // Only the closest states are rendered when there are too many of them.
+        let dot = to_dot(&initial_state, &goal_state, &actions, 2);
+        assert!(dot.contains("    1 [label=\"has_key = true\", color=red];"));
+        assert!(!dot.contains("    2 ["));
+        assert!(!dot.contains("-> 2"));
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! The execution of plans, applying their actions to a world state.
+
+use std::error::Error;
+use std::fmt;
+#[cfg(not(feature = "std"))]
+use prelude::*;
+use search::SearchSpace;
+use {Action, Atom, State};
+
+/// The reasons why a plan couldn't be executed.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum ExecError<K = String> {
+    /// The pre-condition of the named action on this atom isn't fulfilled by the state it's about
+    /// to be applied to.
+    PreconditionFailed { action: String, atom: K },
+}
+
+impl<K: fmt::Display> fmt::Display for ExecError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ExecError::PreconditionFailed { ref action, ref atom } => {
+                write!(f, "the pre-condition of `{}` on `{}` isn't fulfilled", action, atom)
+            }
+        }
+    }
+}
+
+impl<K: fmt::Debug + fmt::Display> Error for ExecError<K> {}
+
+/// Applies the effects of an action to a state, without checking its pre-conditions.
+pub fn apply<K: Atom, C>(action: &Action<K, C>, state: &mut State<K>) {
+    for (name, effect) in &action.post_conditions {
+        if let Some(value) = effect.apply_to(state.get(name)) {
+            state.insert(name.clone(), value);
+        }
+    }
+}
+
+/// Returns each action applicable to a state, along with the state it leads to and its cost, as
+/// the search for a plan sees them.
+///
+/// Actions are listed in the order the search tries them, by name. States aren't deduplicated, so
+/// different actions leading to the same state each come with their own copy of it.
+pub fn successors<'a, K: Atom>(state: &State<K>,
+                               actions: &'a [Action<K>])
+                               -> Vec<(State<K>, &'a Action<K>, usize)> {
+    let space = SearchSpace::new(state, actions, &());
+    space.initial_node()
+        .transitions()
+        .into_iter()
+        .map(|(index, node, cost)| (node.current_state(), &actions[index], cost))
+        .collect()
+}
+
+/// Applies the actions of a plan to a state in order, after checking that the pre-conditions of
+/// each of them are fulfilled.
+///
+/// When an action can't be applied, such as when the world changed in the meantime, the state is
+/// left with the actions before it applied.
+pub fn execute<K: Atom, C>(plan: &[&Action<K, C>],
+                           state: &mut State<K>)
+                           -> Result<(), ExecError<K>> {
+    for action in plan {
+        if let Some(name) = unmet_pre_condition(action, state) {
+            return Err(ExecError::PreconditionFailed {
+                action: action.name.clone(),
+                atom: name.clone(),
+            });
+        }
+
+        apply(action, state);
+    }
+
+    Ok(())
+}
+
+/// Returns the atom of the first pre-condition of an action that a state doesn't fulfill, if any.
+///
+/// When a relational pre-condition isn't fulfilled, its left atom is returned, and when none of
+/// the alternatives of the action are, the first atom of the first alternative the state doesn't
+/// have is.
+pub fn unmet_pre_condition<'a, K: Atom, C>(action: &'a Action<K, C>,
+                                           state: &State<K>)
+                                           -> Option<&'a K> {
+    let unmet_in = |alternative: &'a State<K>| {
+        alternative.iter()
+            .find(|&(name, value)| state.get(name) != Some(value))
+            .map(|(name, _)| name)
+    };
+
+    action.pre_conditions
+        .iter()
+        .find(|&(name, condition)| !condition.is_met_by(state.get(name)))
+        .map(|(name, _)| name)
+        .or_else(|| {
+            action.relational_pre_conditions
+                .iter()
+                .find(|pre| !pre.is_met_by(state.get(&pre.left), state.get(&pre.right)))
+                .map(|pre| &pre.left)
+        })
+        .or_else(|| {
+            let unmet: Vec<&K> = action.any_of.iter().filter_map(unmet_in).collect();
+            if unmet.len() == action.any_of.len() {
+                unmet.first().cloned()
+            } else {
+                None
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Effect, plan};
+
+    #[test]
+    fn test_execute() {
+        let actions = [Action::new("get_key", 1).produces("has_key", true),
+                       Action::new("open_door", 1)
+                           .requires("has_key", true)
+                           .produces("door_open", true),
+                       Action::new("knock", 1).produces("knocks", Effect::Add(1))];
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_key".to_string(), false.into());
+        initial_state.insert("door_open".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("door_open".to_string(), true.into());
+
+        let planned = plan(&initial_state, &goal_state, &actions).unwrap();
+        let mut state = initial_state.clone();
+        assert_eq!(execute(&planned, &mut state), Ok(()));
+        assert_eq!(state["door_open"], true.into());
+
+        apply(&actions[2], &mut state);
+        apply(&actions[2], &mut state);
+        assert_eq!(state["knocks"], 2.into());
+
+        // The world changing under the plan stops it where it can't go on.
+        let mut state = initial_state.clone();
+        let error = execute(&[&actions[1], &actions[0]], &mut state).unwrap_err();
+        assert_eq!(error,
+                   ExecError::PreconditionFailed {
+                       action: "open_door".to_string(),
+                       atom: "has_key".to_string(),
+                   });
+        assert_eq!(error.to_string(),
+                   "the pre-condition of `open_door` on `has_key` isn't fulfilled");
+        assert_eq!(state, initial_state);
+    }
+
+    #[test]
+    fn test_successors() {
+        let actions = [Action::new("open_door", 1)
+                           .requires("has_key", true)
+                           .produces("door_open", true),
+                       Action::new("get_key", 1).produces("has_key", true),
+                       Action::new("knock", 1).costs(|state| {
+                           if state.get("knocks").is_some() { 2 } else { 1 }
+                       }).produces("knocks", Effect::Add(1))];
+
+        let mut state = State::new();
+        state.insert("has_key".to_string(), false.into());
+        state.insert("knocks".to_string(), 0.into());
+
+        let next: Vec<(State, &str, usize)> = successors(&state, &actions)
+            .into_iter()
+            .map(|(state, action, cost)| (state, action.name.as_str(), cost))
+            .collect();
+        let mut has_key = state.clone();
+        has_key.insert("has_key".to_string(), true.into());
+        let mut knocked = state.clone();
+        knocked.insert("knocks".to_string(), 1.into());
+        assert_eq!(next, vec![(has_key, "get_key", 1), (knocked, "knock", 2)]);
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! The generation of synthetic planning domains, for benchmarks and tests.
+
+#[cfg(not(feature = "std"))]
+use prelude::*;
+use {Action, State};
+
+/// A small pseudo-random number generator (SplitMix64), so that domains only depend on their seed.
+pub struct Rng(pub u64);
+
+impl Rng {
+    /// Returns the next number, the first one being the seed with its bits scrambled.
+    pub fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..bound`.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+/// Generates a random planning domain, made of an initial state, a goal state and a set of allowed
+/// actions, which is always the same for the same arguments.
+///
+/// The domain has `num_atoms` boolean atoms, named `atom_0`, `atom_1`, etc., which are all `false`
+/// in the initial state, and `num_actions` actions. Some of those actions make up a chain, each
+/// requiring the atom the previous one produces, which leads to the goal state, so that there's
+/// always a plan. The other actions have random conditions and effects, and are more expensive.
+///
+/// # Panics
+///
+/// Panics if there are no atoms or no actions.
+pub fn generate_domain(num_atoms: usize,
+                       num_actions: usize,
+                       seed: u64)
+                       -> (State, State, Vec<Action>) {
+    assert!(num_atoms > 0 && num_actions > 0, "domains need atoms and actions");
+    let mut rng = Rng(seed);
+    let atom = |index: usize| format!("atom_{}", index);
+
+    let mut initial_state = State::new();
+    for index in 0..num_atoms {
+        initial_state.insert(atom(index), false.into());
+    }
+
+    // The chain goes through distinct atoms, in a random order.
+    let mut chain: Vec<usize> = (0..num_atoms).collect();
+    for index in (1..chain.len()).rev() {
+        chain.swap(index, rng.below(index + 1));
+    }
+    chain.truncate(num_actions.min(8));
+
+    let mut goal_state = State::new();
+    goal_state.insert(atom(chain[chain.len() - 1]), true.into());
+
+    let mut actions: Vec<Action> = chain.iter()
+        .enumerate()
+        .map(|(step, &produced)| {
+            let action = Action::new(format!("step_{}", step), 1).produces(atom(produced), true);
+            if step > 0 {
+                action.requires(atom(chain[step - 1]), true)
+            } else {
+                action
+            }
+        })
+        .collect();
+
+    for index in actions.len()..num_actions {
+        let mut action = Action::new(format!("action_{}", index), 2 + rng.below(8));
+        for _ in 0..rng.below(3) {
+            action = action.requires(atom(rng.below(num_atoms)), rng.below(2) == 0);
+        }
+        for _ in 0..1 + rng.below(2) {
+            action = action.produces(atom(rng.below(num_atoms)), rng.below(2) == 0);
+        }
+        actions.push(action);
+    }
+
+    // The chain actions are spread among the others.
+    for index in (1..actions.len()).rev() {
+        actions.swap(index, rng.below(index + 1));
+    }
+
+    (initial_state, goal_state, actions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plan;
+
+    #[test]
+    fn test_generate_domain() {
+        let (initial_state, goal_state, actions) = generate_domain(16, 50, 7);
+        assert_eq!(initial_state.len(), 16);
+        assert_eq!(actions.len(), 50);
+        assert_eq!(generate_domain(16, 50, 7),
+                   (initial_state.clone(), goal_state.clone(), actions.clone()));
+        assert!(plan(&initial_state, &goal_state, &actions).is_some());
+
+        for seed in 0..20 {
+            let (initial_state, goal_state, actions) = generate_domain(1 + seed as usize, 30, seed);
+            assert!(plan(&initial_state, &goal_state, &actions).is_some());
+        }
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Planning for several of the cheapest plans, rather than only the cheapest one.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+#[cfg(not(feature = "std"))]
+use prelude::*;
+use search::{PlanNode, SearchSpace};
+use {Action, Atom, Plan, State, ToGoal};
+
+/// A path reached by the search, made of the path to its parent and the last step taken.
+struct Record<'s, 'a: 's, K: 's + 'a> {
+    node: PlanNode<'s, 'a, K>,
+    /// The index of the parent record, of the action applied to it, and its cost.
+    parent: Option<(usize, usize, usize)>,
+}
+
+/// Formulates the `k` cheapest plans to get from an initial state to a goal state using a set of
+/// allowed actions, in order of increasing cost.
+///
+/// Plans are distinct by their actions: the same states reached with different actions make
+/// different plans. Plans never go through the same state twice, nor through the goal state before
+/// their last action. When there are fewer than `k` such plans, all of them are returned. As with
+/// `plan`, the plans are the cheapest as long as no action fixes more goal atoms than its cost at
+/// once.
+///
+/// Rather than nodes, the search goes through paths, so that each node can be reached in several
+/// ways. Finding fewer than `k` plans then takes going through every path, which there can be a
+/// lot of in large domains.
+pub fn plan_k_best<'a, G, K>(initial_state: &'a State<K>,
+                             goal_state: &G,
+                             allowed_actions: &'a [Action<K>],
+                             k: usize)
+                             -> Vec<Plan<'a, K>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    let initial = space.initial_node();
+    let mut open = BinaryHeap::new();
+    open.push(Reverse((initial.mismatch_count(&goal), 0, 0)));
+    let mut records = vec![Record {
+                               node: initial,
+                               parent: None,
+                           }];
+
+    let mut plans = vec![];
+    while plans.len() < k {
+        let Reverse((_, g_cost, index)) = match open.pop() {
+            Some(entry) => entry,
+            None => break,
+        };
+
+        let node = records[index].node.clone();
+        if node.matches(&goal) {
+            plans.push(plan_to(&records, index, allowed_actions, g_cost));
+            continue;
+        }
+
+        for (action_index, child, cost) in node.transitions() {
+            if is_on_path(&records, index, &child) {
+                continue;
+            }
+
+            let child_g_cost = g_cost + cost;
+            let f_cost = child_g_cost + child.mismatch_count(&goal);
+            open.push(Reverse((f_cost, child_g_cost, records.len())));
+            records.push(Record {
+                node: child,
+                parent: Some((index, action_index, cost)),
+            });
+        }
+    }
+
+    plans
+}
+
+/// Returns `true` if a node is on the path leading to a record, including the record itself.
+fn is_on_path<K: Atom>(records: &[Record<K>], mut index: usize, node: &PlanNode<K>) -> bool {
+    loop {
+        if records[index].node == *node {
+            return true;
+        }
+        match records[index].parent {
+            Some((parent, _, _)) => index = parent,
+            None => return false,
+        }
+    }
+}
+
+/// Returns the plan made of the steps leading to a record.
+fn plan_to<'a, K>(records: &[Record<K>],
+                  mut index: usize,
+                  allowed_actions: &'a [Action<K>],
+                  cost: usize)
+                  -> Plan<'a, K>
+    where K: Atom
+{
+    let mut steps = vec![];
+    while let Some((parent, action_index, step_cost)) = records[index].parent {
+        steps.push((&allowed_actions[action_index], step_cost));
+        index = parent;
+    }
+
+    steps.reverse();
+    let (actions, step_costs) = steps.into_iter().unzip();
+    Plan {
+        actions,
+        step_costs,
+        cost,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plan_with_cost;
+    use tests::key_and_door;
+
+    #[test]
+    fn test_plan_k_best() {
+        let (initial_state, goal_state, mut actions) = key_and_door();
+        actions.push(Action::new("pick_lock", 3).produces("door_open", true));
+        let plans = plan_k_best(&initial_state, &goal_state, &actions, 2);
+        assert_eq!(plans[0], plan_with_cost(&initial_state, &goal_state, &actions).unwrap());
+        assert_eq!(plans[1].names(), vec!["pick_lock"]);
+        assert_eq!(plans.len(), 2);
+
+        // Getting the key before using another way in also makes for distinct plans.
+        let plans: Vec<(Vec<String>, usize)> = plan_k_best(&initial_state,
+                                                           &goal_state,
+                                                           &actions,
+                                                           10)
+            .iter()
+            .map(|plan| (plan.names(), plan.cost))
+            .collect();
+        assert_eq!(plans,
+                   vec![(vec!["get_key".to_string(), "open_door".to_string()], 2),
+                        (vec!["pick_lock".to_string()], 3),
+                        (vec!["get_key".to_string(), "pick_lock".to_string()], 4),
+                        (vec!["smash_door".to_string()], 5),
+                        (vec!["get_key".to_string(), "smash_door".to_string()], 6)]);
+
+        assert!(plan_k_best(&initial_state, &goal_state, &actions, 0).is_empty());
+    }
+}
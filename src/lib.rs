@@ -16,223 +16,4387 @@
 //! ```rust
 //! extern crate rgoap;
 //!
-//! use rgoap::{State, Action, plan};
+//! use rgoap::{Action, plan_with_cost, state};
 //!
 //! # fn main() {
 //! // The actions your planner will be allowed to use.
-//! let mut walk_to_dog = Action::new("walk_to_dog".to_string(), 1);
-//! walk_to_dog.pre_conditions.insert("dog_person".to_string(), true);
-//! walk_to_dog.post_conditions.insert("near_dog".to_string(), true);
+//! let walk_to_dog = Action::new("walk_to_dog", 1)
+//!     .requires("dog_person", true)
+//!     .produces("near_dog", true);
 //!
-//! let mut dog_wiggles_tail = Action::new("dog_wiggles_tail".to_string(), 1);
-//! dog_wiggles_tail.pre_conditions.insert("dog_happy".to_string(), true);
-//! dog_wiggles_tail.post_conditions.insert("tails_wiggling".to_string(), true);
+//! let dog_wiggles_tail = Action::new("dog_wiggles_tail", 1)
+//!     .requires("dog_happy", true)
+//!     .produces("tails_wiggling", true);
 //!
-//! let mut pet_dog = Action::new("pet_dog".to_string(), 1);
-//! pet_dog.pre_conditions.insert("near_dog".to_string(), true);
-//! pet_dog.post_conditions.insert("dog_happy".to_string(), true);
+//! let pet_dog = Action::new("pet_dog", 1)
+//!     .requires("near_dog", true)
+//!     .produces("dog_happy", true);
 //!
 //! let possible_actions = [walk_to_dog, pet_dog, dog_wiggles_tail];
 //!
 //! // This is the initial state of the world.
-//! let mut initial_state = State::new();
-//! initial_state.insert("near_dog".to_string(), false);
-//! initial_state.insert("dog_person".to_string(), true);
-//! initial_state.insert("dog_happy".to_string(), false);
-//! initial_state.insert("tails_wiggling".to_string(), false);
+//! let initial_state = state([("near_dog", false),
+//!                            ("dog_person", true),
+//!                            ("dog_happy", false),
+//!                            ("tails_wiggling", false)]);
 //!
 //! // And this is the target state. Note that it doesn't have to include all of the states.
-//! let mut goal_state = State::new();
-//! goal_state.insert("tails_wiggling".to_string(), true);
+//! let goal_state = state([("tails_wiggling", true)]);
 //!
 //! // Let's find which actions needs to happen to get there.
-//! let planned_actions = plan(&initial_state, &goal_state, &possible_actions).unwrap();
+//! let planned = plan_with_cost(&initial_state, &goal_state, &possible_actions).unwrap();
 //!
 //! // Are the actions what we expected?
-//! let planned_actions_names: Vec<String> =
-//!     planned_actions.iter().map(|&action| action.name.clone()).collect();
-//! let expected_actions_names =
-//!     vec!["walk_to_dog".to_string(), "pet_dog".to_string(), "dog_wiggles_tail".to_string()];
-//! assert_eq!(planned_actions_names, expected_actions_names);
+//! assert_eq!(planned.names(), vec!["walk_to_dog", "pet_dog", "dog_wiggles_tail"]);
+//! assert_eq!(planned.to_string(), "walk_to_dog -> pet_dog -> dog_wiggles_tail (cost 3)");
 //! # }
 //! ```
+//!
+//! ## Without the standard library
+//!
+//! The crate builds for `no_std` targets with an allocator when its default `std` feature is
+//! turned off:
+//!
+//! ```toml
+//! [dependencies]
+//! rgoap = { version = "0.1", default-features = false }
+//! ```
+//!
+//! States, goals and actions are then still available, along with `plan`, `plan_with_cost` and the
+//! other planning functions searching forward from the costs of the actions, such as
+//! `plan_with_heuristic`, `plan_until`, `plan_with_limits` or `plan_k_best`, as well as
+//! `check_domain`, `apply` and `execute`. Those search with a best-first loop of the crate's own
+//! instead of the A* of `pathfinding`, which expands states in the same order, so that plans don't
+//! depend on the feature.
+//!
+//! The rest needs the standard library: the other searches built on `pathfinding`, such as
+//! `plan_all_optimal`, `plan_backward`, `plan_ida_star` or `plan_with_real_costs`, what relies on
+//! hash maps or the clock, such as `plan_with_stats` or `plan_with_timeout`, the `Planner` and its
+//! mutex, as well as `Domain` and the `use_serde`, `rayon` and `im` features, which turn `std` on.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
 #[macro_use]
 #[cfg(feature = "use_serde")]
 extern crate serde_derive;
 #[cfg(feature = "use_serde")]
 extern crate serde;
+#[cfg(feature = "std")]
 extern crate pathfinding;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "im")]
+extern crate im;
+
+/// The parts of the standard library the crate uses, out of `core` and `alloc` without it.
+#[cfg(not(feature = "std"))]
+mod std {
+    pub use core::{cmp, error, fmt, hash, ops};
+    pub use alloc::{collections, sync};
+}
+
+/// What the standard prelude brings in, out of `alloc` without the standard library.
+#[cfg(not(feature = "std"))]
+mod prelude {
+    pub use alloc::boxed::Box;
+    pub use alloc::string::String;
+    #[cfg(test)]
+    pub use alloc::string::ToString;
+    pub use alloc::vec::Vec;
+}
+
+#[cfg(feature = "std")]
+mod anytime;
+#[cfg(feature = "std")]
+mod bits;
+#[cfg(feature = "use_serde")]
+mod data;
+mod domain;
+#[cfg(feature = "std")]
+mod dot;
+mod execute;
+mod generate;
+mod k_best;
+mod namespace;
+#[cfg(feature = "std")]
+mod planner;
+mod provider;
+mod search;
+
+#[cfg(feature = "std")]
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+#[cfg(feature = "std")]
+use generate::Rng;
+#[cfg(not(feature = "std"))]
+use prelude::*;
+use search::{SearchSpace, path_action_indices, path_actions, path_steps};
+#[cfg(feature = "std")]
+use search::path_index_sequences;
+
+#[cfg(feature = "std")]
+pub use anytime::{AnytimePlans, plan_anytime};
+#[cfg(feature = "std")]
+pub use bits::{BitState, MAX_SCHEMA_ATOMS, StateSchema, plan_dense};
+#[cfg(feature = "use_serde")]
+pub use data::{Domain, DomainError};
+pub use domain::{Applicable, DomainWarning, applicability_report, check_domain,
+                 explain_failure, is_relevant, used_actions_closure};
+#[cfg(feature = "std")]
+pub use dot::to_dot;
+pub use execute::{ExecError, apply, execute, successors};
+pub use generate::generate_domain;
+pub use k_best::plan_k_best;
+pub use namespace::{Namespaces, Prefixed};
+#[cfg(feature = "std")]
+pub use planner::{CacheStats, MAX_PENALIZED_USES, Planner, RepeatPenalty};
+pub use provider::{ActionProvider, plan_with_provider};
+
+/// The value of a state atom.
+///
+/// When serialized, booleans and integers are written as is, while enumerated values are written
+/// as an `{ "enum": <value> }` object, so that they can be told apart from integers.
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "use_serde", serde(untagged))]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub enum Value {
+    /// A boolean fact, such as whether a door is open.
+    Bool(bool),
+    /// An integer quantity, such as an amount of wood.
+    Int(i64),
+    /// One among a set of exclusive values, such as the kind of weapon being held.
+    Enum(#[cfg_attr(feature = "use_serde", serde(with = "enum_value"))] u32),
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Value {
+        Value::Bool(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Value {
+        Value::Int(value)
+    }
+}
+
+/// Serialization of enumerated values as `{ "enum": <value> }` objects.
+#[cfg(feature = "use_serde")]
+mod enum_value {
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct EnumValue {
+        #[serde(rename = "enum")]
+        value: u32,
+    }
+
+    pub fn serialize<S>(value: &u32, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        EnumValue { value: *value }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u32, D::Error>
+        where D: Deserializer<'de>
+    {
+        EnumValue::deserialize(deserializer).map(|enum_value| enum_value.value)
+    }
+}
+
+/// Serialization of present goal atoms as a `"present"` string.
+#[cfg(feature = "use_serde")]
+mod present {
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+    use serde::de::Error;
+
+    pub fn serialize<S>(serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        "present".serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<(), D::Error>
+        where D: Deserializer<'de>
+    {
+        match String::deserialize(deserializer)?.as_str() {
+            "present" => Ok(()),
+            other => Err(D::Error::custom(format!("expected \"present\", found {:?}", other))),
+        }
+    }
+}
+
+/// Serialization of toggling effects as a `"toggle"` string.
+#[cfg(feature = "use_serde")]
+mod toggle {
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+    use serde::de::Error;
+
+    pub fn serialize<S>(serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        "toggle".serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<(), D::Error>
+        where D: Deserializer<'de>
+    {
+        match String::deserialize(deserializer)?.as_str() {
+            "toggle" => Ok(()),
+            other => Err(D::Error::custom(format!("expected \"toggle\", found {:?}", other))),
+        }
+    }
+}
+
+/// The type of the names of state atoms, which are strings by default.
+///
+/// Any ordered type can be used instead, such as an enumeration of the atoms of a domain, or
+/// identifiers of symbols interned by the application, which are cheaper to copy than strings.
+pub trait Atom: Ord + Clone {}
+
+impl<T: Ord + Clone> Atom for T {}
+
+/// A map of state atoms to their values.
+pub type State<K = String> = BTreeMap<K, Value>;
+
+/// Makes a state out of pairs of atom names and values, as in
+/// `state([("near_dog", false), ("dog_person", true)])`.
+///
+/// Values of different types can be mixed by making them `Value`s first. The state is a map like
+/// any other, so more atoms can be inserted in it afterward.
+pub fn state<I, S, V>(atoms: I) -> State
+    where I: IntoIterator<Item = (S, V)>,
+          S: Into<String>,
+          V: Into<Value>
+{
+    atoms.into_iter().map(|(name, value)| (name.into(), value.into())).collect()
+}
+
+/// Returns the atoms whose values differ from one state to the other, in order, along with their
+/// old and new values, which are `None` when the atom is absent from the state.
+pub fn state_delta<K: Atom>(old: &State<K>,
+                            new: &State<K>)
+                            -> Vec<(K, Option<Value>, Option<Value>)> {
+    let names: BTreeSet<&K> = old.keys().chain(new.keys()).collect();
+    names.into_iter()
+        .map(|name| (name, old.get(name).cloned(), new.get(name).cloned()))
+        .filter(|&(_, old_value, new_value)| old_value != new_value)
+        .map(|(name, old_value, new_value)| (name.clone(), old_value, new_value))
+        .collect()
+}
+
+/// A 64-bit FNV-1a hasher, whose hashes don't depend on the process, the platform, or the version
+/// of Rust.
+struct FnvHasher(u64);
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+/// Hashes a state, so that it can be used as a cache key, such as to tell whether the world
+/// changed since the last plan.
+///
+/// Equal states have the same hash, which is the same from one run to the next, as long as the
+/// atoms hash the same way, as strings do.
+pub fn hash_state<K: Atom + Hash>(state: &State<K>) -> u64 {
+    let mut hasher = FnvHasher(0xcbf2_9ce4_8422_2325);
+    for (name, value) in state {
+        name.hash(&mut hasher);
+        // Values are written out by hand, as derived hashes write discriminants as `isize`.
+        match *value {
+            Value::Bool(value) => hasher.write(&[0, value as u8]),
+            Value::Int(value) => {
+                hasher.write(&[1]);
+                hasher.write(&value.to_le_bytes());
+            }
+            Value::Enum(value) => {
+                hasher.write(&[2]);
+                hasher.write(&value.to_le_bytes());
+            }
+        }
+    }
+
+    hasher.finish()
+}
+
+/// A requirement on a state atom for a goal to be reached.
+///
+/// When serialized, required values are written as is, absent atoms are written as `null`, and
+/// present atoms are written as `"present"`.
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "use_serde", serde(untagged))]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum GoalAtom {
+    /// The atom must have this value.
+    Required(Value),
+    /// The atom must be absent from the state. As plans start from the initial state, and as
+    /// effects never remove atoms from it, this means that the atom must neither be part of the
+    /// initial state nor be produced by any planned action.
+    MustBeAbsent,
+    /// The atom must be part of the state, whatever its value. As plans start from the initial
+    /// state, atoms of the initial state are always present, so this is only useful for atoms
+    /// which are produced by actions.
+    #[cfg_attr(feature = "use_serde", serde(with = "present"))]
+    Present,
+}
+
+impl GoalAtom {
+    /// Returns `true` if the given atom value, if any, fulfills this goal atom.
+    pub fn is_met_by(&self, value: Option<&Value>) -> bool {
+        match *self {
+            GoalAtom::Required(ref expected) => value == Some(expected),
+            GoalAtom::MustBeAbsent => value.is_none(),
+            GoalAtom::Present => value.is_some(),
+        }
+    }
+
+    /// Returns `true` if the given atom value, if any, fulfills this goal atom, with an absent atom
+    /// handled as told by `on_missing`.
+    pub fn is_met_with(&self, value: Option<&Value>, on_missing: OnMissing) -> bool {
+        match (on_missing, value, *self) {
+            (OnMissing::TreatAsFalse, None, GoalAtom::Required(Value::Bool(false))) => true,
+            _ => self.is_met_by(value),
+        }
+    }
+}
+
+/// How goal atoms requiring an atom to be `false` handle the atom being absent from the state.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Default, Debug)]
+pub enum OnMissing {
+    /// An absent atom doesn't fulfill the goal atom, as it doesn't have the required value. This is
+    /// what planning does unless told otherwise.
+    #[default]
+    CountAsMismatch,
+    /// An absent atom fulfills the goal atom, for domains where atoms are only added to the state
+    /// once they're `true`. Other goal atoms are left as they are.
+    TreatAsFalse,
+}
+
+impl<T: Into<Value>> From<T> for GoalAtom {
+    fn from(value: T) -> GoalAtom {
+        GoalAtom::Required(value.into())
+    }
+}
+
+/// A map of state atoms to the requirements they must fulfill for a goal to be reached.
+pub type Goal<K = String> = BTreeMap<K, GoalAtom>;
+
+/// Something that can be planned toward, such as a goal state, or a `Goal` map.
+///
+/// A goal state requires each of its atoms to have the value it holds.
+pub trait ToGoal<K: Atom = String> {
+    /// Returns the goal atoms to reach.
+    fn to_goal(&self) -> Goal<K>;
+}
+
+impl<K: Atom> ToGoal<K> for State<K> {
+    fn to_goal(&self) -> Goal<K> {
+        self.iter().map(|(name, value)| (name.clone(), GoalAtom::Required(*value))).collect()
+    }
+}
+
+impl<K: Atom> ToGoal<K> for Goal<K> {
+    fn to_goal(&self) -> Goal<K> {
+        self.clone()
+    }
+}
+
+/// Two goal fragments requiring different values for the same atom, as found by `merge_goals`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct GoalConflict<K = String> {
+    /// The atom both fragments have a value for.
+    pub atom: K,
+    /// The value of the atom in the first fragment having it.
+    pub first: Value,
+    /// The value of the atom in the fragment conflicting with the first one.
+    pub second: Value,
+}
+
+impl<K: fmt::Display> fmt::Display for GoalConflict<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+               "`{}` is required to be both {:?} and {:?}",
+               self.atom,
+               self.first,
+               self.second)
+    }
+}
+
+impl<K: fmt::Debug + fmt::Display> Error for GoalConflict<K> {}
+
+/// Merges goal states, such as ones coming from different subsystems, into a single goal state.
+///
+/// Fragments can require the same value for an atom, but requiring different values for it is a
+/// conflict, and the first one of those is returned.
+pub fn merge_goals<K: Atom>(fragments: &[State<K>]) -> Result<State<K>, GoalConflict<K>> {
+    let mut merged = State::new();
+    for fragment in fragments {
+        for (atom, value) in fragment {
+            match merged.get(atom) {
+                Some(first) if first != value => {
+                    return Err(GoalConflict {
+                        atom: atom.clone(),
+                        first: *first,
+                        second: *value,
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    merged.insert(atom.clone(), *value);
+                }
+            }
+        }
+    }
+
+    Ok(merged)
+}
+
+/// Returns the goal atoms which the initial state doesn't fulfill, but the final state does, such
+/// as to tell what a plan achieves.
+pub fn goals_achieved<G, K>(initial_state: &State<K>,
+                            goal_state: &G,
+                            final_state: &State<K>)
+                            -> Vec<K>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    goal_state.to_goal()
+        .into_iter()
+        .filter(|(name, goal_atom)| {
+            !goal_atom.is_met_by(initial_state.get(name)) &&
+            goal_atom.is_met_by(final_state.get(name))
+        })
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// A goal made of hard goal atoms, which must all be fulfilled, and of soft ones, which are only
+/// nice to have.
+///
+/// Planning toward a goal spec with `plan_with_spec` fulfills all the hard goal atoms, and as many
+/// of the soft ones as possible among the cheapest plans. Elsewhere, only the hard goal atoms are
+/// planned toward.
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct GoalSpec<K: Atom = String> {
+    pub hard: Goal<K>,
+    pub soft: Goal<K>,
+}
+
+impl<K: Atom> GoalSpec<K> {
+    /// Makes a goal spec without any goal atoms.
+    pub fn new() -> GoalSpec<K> {
+        GoalSpec {
+            hard: Goal::new(),
+            soft: Goal::new(),
+        }
+    }
+
+    /// Adds a hard goal atom to this goal spec.
+    pub fn requires<S: Into<K>, T: Into<GoalAtom>>(mut self, name: S, goal_atom: T) -> GoalSpec<K> {
+        self.hard.insert(name.into(), goal_atom.into());
+        self
+    }
+
+    /// Adds a soft goal atom to this goal spec.
+    pub fn prefers<S: Into<K>, T: Into<GoalAtom>>(mut self, name: S, goal_atom: T) -> GoalSpec<K> {
+        self.soft.insert(name.into(), goal_atom.into());
+        self
+    }
+}
+
+impl<K: Atom> Default for GoalSpec<K> {
+    fn default() -> GoalSpec<K> {
+        GoalSpec::new()
+    }
+}
+
+impl<K: Atom> ToGoal<K> for GoalSpec<K> {
+    fn to_goal(&self) -> Goal<K> {
+        self.hard.clone()
+    }
+}
+
+/// A requirement on the value of a state atom.
+///
+/// When serialized, equality conditions are written as the expected value, while comparisons are
+/// written as an `{ "<operator>": <value> }` object, such as `{ ">=": 3 }`.
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "use_serde", serde(from = "ConditionRepr", into = "ConditionRepr"))]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Condition {
+    /// The atom must have exactly this value.
+    Equal(Value),
+    /// The atom must be an integer greater than or equal to this one.
+    GreaterOrEqual(i64),
+    /// The atom must be an integer strictly greater than this one.
+    Greater(i64),
+    /// The atom must be an integer less than or equal to this one.
+    LessOrEqual(i64),
+    /// The atom must be an integer strictly less than this one.
+    Less(i64),
+}
+
+impl Condition {
+    /// Returns `true` if the given atom value, if any, fulfills this condition.
+    pub fn is_met_by(&self, value: Option<&Value>) -> bool {
+        match (*self, value) {
+            (Condition::Equal(ref expected), Some(value)) => value == expected,
+            (Condition::GreaterOrEqual(bound), Some(&Value::Int(value))) => value >= bound,
+            (Condition::Greater(bound), Some(&Value::Int(value))) => value > bound,
+            (Condition::LessOrEqual(bound), Some(&Value::Int(value))) => value <= bound,
+            (Condition::Less(bound), Some(&Value::Int(value))) => value < bound,
+            _ => false,
+        }
+    }
+}
+
+impl<T: Into<Value>> From<T> for Condition {
+    fn from(value: T) -> Condition {
+        Condition::Equal(value.into())
+    }
+}
+
+/// A pre-condition comparing the values of two state atoms to each other, rather than the value of
+/// an atom to a constant.
+///
+/// The pre-condition is never fulfilled when either atom is absent from the state, whether the
+/// atoms must have the same value or not.
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct RelationalPre<K = String> {
+    pub left: K,
+    pub right: K,
+    /// Whether the atoms must have the same value, rather than different ones.
+    pub equal: bool,
+}
+
+impl<K: Atom> RelationalPre<K> {
+    /// Returns `true` if the given atom values, if any, fulfill this pre-condition.
+    pub fn is_met_by(&self, left: Option<&Value>, right: Option<&Value>) -> bool {
+        match (left, right) {
+            (Some(left), Some(right)) => (left == right) == self.equal,
+            _ => false,
+        }
+    }
+}
+
+/// A change applied to the value of a state atom.
+///
+/// When serialized, absolute effects are written as the assigned value, relative effects are
+/// written as an `{ "add": <delta> }` object, and toggles are written as `"toggle"`.
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "use_serde", serde(from = "EffectRepr", into = "EffectRepr"))]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Effect {
+    /// Assigns this value to the atom.
+    Set(Value),
+    /// Adds this delta to an integer atom. An absent atom counts as zero, and an atom holding a
+    /// non-integer value is left untouched.
+    Add(i64),
+    /// Inverts the value of a boolean atom, such as when flipping a switch. An absent atom counts
+    /// as `false`, and an atom holding a non-boolean value is left untouched.
+    Toggle,
+}
+
+impl Effect {
+    /// Returns the value of an atom after applying this effect to its current value, if any.
+    pub fn apply_to(&self, value: Option<&Value>) -> Option<Value> {
+        match (*self, value) {
+            (Effect::Set(value), _) => Some(value),
+            (Effect::Add(delta), Some(&Value::Int(value))) => {
+                Some(Value::Int(value.saturating_add(delta)))
+            }
+            (Effect::Add(delta), None) => Some(Value::Int(delta)),
+            (Effect::Add(_), Some(value)) => Some(*value),
+            (Effect::Toggle, Some(&Value::Bool(value))) => Some(Value::Bool(!value)),
+            (Effect::Toggle, None) => Some(Value::Bool(true)),
+            (Effect::Toggle, Some(value)) => Some(*value),
+        }
+    }
+}
+
+impl<T: Into<Value>> From<T> for Effect {
+    fn from(value: T) -> Effect {
+        Effect::Set(value.into())
+    }
+}
+
+/// Returns `true` if a flag isn't set, so that it's only serialized when it is.
+#[cfg(feature = "use_serde")]
+fn is_false(flag: &bool) -> bool {
+    !*flag
+}
+
+/// Returns `true` if a preference is neutral, so that it's only serialized when it isn't.
+#[cfg(feature = "use_serde")]
+fn is_neutral(preference: &i32) -> bool {
+    *preference == 0
+}
+
+/// Serialized forms of the conditions and effects.
+#[cfg(feature = "use_serde")]
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+enum ConditionRepr {
+    Equal(Value),
+    Compare(ComparisonRepr),
+}
+
+#[cfg(feature = "use_serde")]
+#[derive(Serialize, Deserialize, Clone)]
+enum ComparisonRepr {
+    #[serde(rename = ">=")]
+    GreaterOrEqual(i64),
+    #[serde(rename = ">")]
+    Greater(i64),
+    #[serde(rename = "<=")]
+    LessOrEqual(i64),
+    #[serde(rename = "<")]
+    Less(i64),
+}
+
+#[cfg(feature = "use_serde")]
+impl From<ConditionRepr> for Condition {
+    fn from(repr: ConditionRepr) -> Condition {
+        match repr {
+            ConditionRepr::Equal(value) => Condition::Equal(value),
+            ConditionRepr::Compare(ComparisonRepr::GreaterOrEqual(bound)) => {
+                Condition::GreaterOrEqual(bound)
+            }
+            ConditionRepr::Compare(ComparisonRepr::Greater(bound)) => Condition::Greater(bound),
+            ConditionRepr::Compare(ComparisonRepr::LessOrEqual(bound)) => {
+                Condition::LessOrEqual(bound)
+            }
+            ConditionRepr::Compare(ComparisonRepr::Less(bound)) => Condition::Less(bound),
+        }
+    }
+}
+
+#[cfg(feature = "use_serde")]
+impl From<Condition> for ConditionRepr {
+    fn from(condition: Condition) -> ConditionRepr {
+        match condition {
+            Condition::Equal(value) => ConditionRepr::Equal(value),
+            Condition::GreaterOrEqual(bound) => {
+                ConditionRepr::Compare(ComparisonRepr::GreaterOrEqual(bound))
+            }
+            Condition::Greater(bound) => ConditionRepr::Compare(ComparisonRepr::Greater(bound)),
+            Condition::LessOrEqual(bound) => {
+                ConditionRepr::Compare(ComparisonRepr::LessOrEqual(bound))
+            }
+            Condition::Less(bound) => ConditionRepr::Compare(ComparisonRepr::Less(bound)),
+        }
+    }
+}
+
+#[cfg(feature = "use_serde")]
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+enum EffectRepr {
+    Set(Value),
+    Add { add: i64 },
+    #[serde(with = "toggle")]
+    Toggle,
+}
+
+#[cfg(feature = "use_serde")]
+impl From<EffectRepr> for Effect {
+    fn from(repr: EffectRepr) -> Effect {
+        match repr {
+            EffectRepr::Set(value) => Effect::Set(value),
+            EffectRepr::Add { add } => Effect::Add(add),
+            EffectRepr::Toggle => Effect::Toggle,
+        }
+    }
+}
+
+#[cfg(feature = "use_serde")]
+impl From<Effect> for EffectRepr {
+    fn from(effect: Effect) -> EffectRepr {
+        match effect {
+            Effect::Set(value) => EffectRepr::Set(value),
+            Effect::Add(add) => EffectRepr::Add { add },
+            Effect::Toggle => EffectRepr::Toggle,
+        }
+    }
+}
+
+/// A map of state atoms to the conditions their values must fulfill.
+pub type Conditions<K = String> = BTreeMap<K, Condition>;
+
+/// A map of state atoms to the effects changing their values.
+pub type Effects<K = String> = BTreeMap<K, Effect>;
+
+/// A check of whether an action is applicable given some context.
+///
+/// Checks are shared between the clones of an action, and between threads.
+pub type ContextCheck<C> = Arc<dyn Fn(&C) -> bool + Send + Sync>;
+
+/// A function telling the cost of an action given the state it's applied to, and the context
+/// planned in.
+///
+/// Cost functions are shared between the clones of an action, and between threads.
+pub type CostFn<K = String, C = ()> = Arc<dyn Fn(&State<K>, &C) -> usize + Send + Sync>;
+
+/// An action that can be used to influence the world state.
+///
+/// All of the pre-conditions of an action must be fulfilled for it to be applicable, along with
+/// one of its alternatives, when it has some, such as having either a key or a lockpick to open a
+/// door. Besides its pre-conditions on the world state, an action can have a context check, which
+/// tells whether it's applicable given some context of type `C` that the world state doesn't
+/// capture, such as whether a target is within range. Actions with a context check are planned for
+/// with `plan_in_context`.
+///
+/// An action can also have a cost function, whose result is used instead of its static cost, so
+/// that its cost can depend on the state it's applied to, and on the context. It can be applicable
+/// only once in a plan, such as when it uses up an item, or belong to a group of actions among
+/// which only one can be applied in a plan, such as when picking a weapon. Its description and
+/// tags are left alone by the planner, and are only there for presenting plans.
+///
+/// The atoms the action refers to are of type `K`, see `Atom`.
+///
+/// Actions are `Send` and `Sync` as long as their atoms are, since their context checks and cost
+/// functions have to be. A set of actions can then be shared by threads planning concurrently,
+/// such as through an `Arc<[Action]>`.
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+pub struct Action<K: Atom = String, C = ()> {
+    pub name: String,
+    pub cost: usize,
+    pub pre_conditions: Conditions<K>,
+    pub post_conditions: Effects<K>,
+    /// The pre-conditions comparing atoms to each other.
+    #[cfg_attr(feature = "use_serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub relational_pre_conditions: Vec<RelationalPre<K>>,
+    /// Alternative atom values, at least one set of which the state must have for the action to
+    /// be applicable, unless there are none.
+    #[cfg_attr(feature = "use_serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub any_of: Vec<State<K>>,
+    /// Whether the action can only be applied once in a plan.
+    #[cfg_attr(feature = "use_serde", serde(default, skip_serializing_if = "is_false"))]
+    pub once: bool,
+    /// The group of the action, if any. Once an action of a group is applied, no other action of
+    /// the same group can be applied in the rest of the plan.
+    #[cfg_attr(feature = "use_serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub group: Option<String>,
+    /// How much the action is preferred over others, or discouraged when negative, when choosing
+    /// between plans sharing the optimal cost with `TieBreak::Preferred`.
+    #[cfg_attr(feature = "use_serde", serde(default, skip_serializing_if = "is_neutral"))]
+    pub preference: i32,
+    /// A human-readable description of the action, which the planner doesn't use.
+    #[cfg_attr(feature = "use_serde", serde(default, skip_serializing_if = "String::is_empty"))]
+    pub description: String,
+    /// Tags to sort or filter actions by when presenting them, which the planner doesn't use.
+    #[cfg_attr(feature = "use_serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub tags: Vec<String>,
+    #[cfg_attr(feature = "use_serde", serde(skip))]
+    pub context_check: Option<ContextCheck<C>>,
+    #[cfg_attr(feature = "use_serde", serde(skip))]
+    pub cost_fn: Option<CostFn<K, C>>,
+}
+
+impl Action {
+    pub fn new<S: Into<String>>(name: S, cost: usize) -> Action {
+        Action::generic(name, cost)
+    }
+}
+
+impl<K: Atom, C> Action<K, C> {
+    /// Makes an action like `new` does, for use with a context of type `C`, and with atoms of
+    /// type `K`.
+    pub fn generic<S: Into<String>>(name: S, cost: usize) -> Action<K, C> {
+        Action {
+            name: name.into(),
+            cost,
+            pre_conditions: Conditions::new(),
+            post_conditions: Effects::new(),
+            relational_pre_conditions: vec![],
+            any_of: vec![],
+            once: false,
+            group: None,
+            preference: 0,
+            description: String::new(),
+            tags: vec![],
+            context_check: None,
+            cost_fn: None,
+        }
+    }
+
+    /// Sets the context check of this action, and returns it so that calls can be chained.
+    pub fn checks<F>(mut self, check: F) -> Action<K, C>
+        where F: Fn(&C) -> bool + Send + Sync + 'static
+    {
+        self.context_check = Some(Arc::new(check));
+        self
+    }
+
+    /// Sets the cost function of this action, and returns it so that calls can be chained.
+    ///
+    /// A plan is only guaranteed to be optimal when the search heuristic never overestimates the
+    /// cost left to pay, so a cost function returning less than the number of goal atoms fixed by
+    /// the action should come with a heuristic that accounts for it, see `plan_with_heuristic`.
+    pub fn costs<F>(self, cost_fn: F) -> Action<K, C>
+        where F: Fn(&State<K>) -> usize + Send + Sync + 'static
+    {
+        self.costs_in_context(move |state, _| cost_fn(state))
+    }
+
+    /// Sets the cost function of this action, which also depends on the context planned in, and
+    /// returns it so that calls can be chained, see `costs`.
+    pub fn costs_in_context<F>(mut self, cost_fn: F) -> Action<K, C>
+        where F: Fn(&State<K>, &C) -> usize + Send + Sync + 'static
+    {
+        self.cost_fn = Some(Arc::new(cost_fn));
+        self
+    }
+
+    /// Adds a pre-condition to this action, and returns it so that calls can be chained.
+    pub fn requires<S, T>(mut self, name: S, condition: T) -> Action<K, C>
+        where S: Into<K>,
+              T: Into<Condition>
+    {
+        self.pre_conditions.insert(name.into(), condition.into());
+        self
+    }
+
+    /// Adds a post-condition to this action, and returns it so that calls can be chained.
+    pub fn produces<S, E>(mut self, name: S, effect: E) -> Action<K, C>
+        where S: Into<K>,
+              E: Into<Effect>
+    {
+        self.post_conditions.insert(name.into(), effect.into());
+        self
+    }
+
+    /// Adds a pre-condition requiring two atoms to have the same value, and returns this action so
+    /// that calls can be chained.
+    pub fn requires_equal<S: Into<K>, T: Into<K>>(mut self, left: S, right: T) -> Action<K, C> {
+        self.relational_pre_conditions.push(RelationalPre {
+            left: left.into(),
+            right: right.into(),
+            equal: true,
+        });
+        self
+    }
+
+    /// Adds a pre-condition requiring two atoms to have different values, and returns this action
+    /// so that calls can be chained.
+    pub fn requires_different<S: Into<K>, T: Into<K>>(mut self, left: S, right: T) -> Action<K, C> {
+        self.relational_pre_conditions.push(RelationalPre {
+            left: left.into(),
+            right: right.into(),
+            equal: false,
+        });
+        self
+    }
+
+    /// Adds an alternative to this action, which is applicable when the state has all the atom
+    /// values of any of its alternatives, and returns it so that calls can be chained.
+    pub fn or_requires(mut self, alternative: State<K>) -> Action<K, C> {
+        self.any_of.push(alternative);
+        self
+    }
+
+    /// Makes this action applicable only once in a plan, and returns it so that calls can be
+    /// chained.
+    pub fn once(mut self) -> Action<K, C> {
+        self.once = true;
+        self
+    }
+
+    /// Makes this action part of a group, and returns it so that calls can be chained.
+    pub fn in_group<S: Into<String>>(mut self, group: S) -> Action<K, C> {
+        self.group = Some(group.into());
+        self
+    }
+
+    /// Sets the preference of this action, and returns it so that calls can be chained.
+    pub fn preferred(mut self, preference: i32) -> Action<K, C> {
+        self.preference = preference;
+        self
+    }
+
+    /// Sets the description of this action, and returns it so that calls can be chained.
+    pub fn described<S: Into<String>>(mut self, description: S) -> Action<K, C> {
+        self.description = description.into();
+        self
+    }
+
+    /// Adds a tag to this action, and returns it so that calls can be chained.
+    pub fn tagged<S: Into<String>>(mut self, tag: S) -> Action<K, C> {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Returns `true` if this action has the given tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|own_tag| own_tag == tag)
+    }
+
+    /// Checks this action for authoring mistakes, such as effects that can't change anything.
+    pub fn validate(&self) -> Result<(), ActionError<K>> {
+        if self.post_conditions.is_empty() {
+            return Err(ActionError::NoPostConditions);
+        }
+
+        for (name, effect) in &self.post_conditions {
+            let redundant = match (*effect, self.pre_conditions.get(name)) {
+                (Effect::Add(0), _) => true,
+                (Effect::Set(value), Some(&Condition::Equal(required))) => value == required,
+                _ => false,
+            };
+            if redundant {
+                return Err(ActionError::RedundantEffect(name.clone()));
+            }
+        }
+
+        if self.cost == 0 && self.cost_fn.is_none() {
+            return Err(ActionError::ZeroCost);
+        }
+
+        Ok(())
+    }
+}
+
+// Functions can't be compared, so only whether actions have one is.
+impl<K: Atom, C> PartialEq for Action<K, C> {
+    fn eq(&self, other: &Action<K, C>) -> bool {
+        self.name == other.name && self.cost == other.cost &&
+        self.pre_conditions == other.pre_conditions &&
+        self.post_conditions == other.post_conditions &&
+        self.relational_pre_conditions == other.relational_pre_conditions &&
+        self.any_of == other.any_of &&
+        self.once == other.once &&
+        self.group == other.group &&
+        self.preference == other.preference &&
+        self.description == other.description &&
+        self.tags == other.tags &&
+        self.context_check.is_some() == other.context_check.is_some() &&
+        self.cost_fn.is_some() == other.cost_fn.is_some()
+    }
+}
+
+impl<K: Atom, C> Eq for Action<K, C> {}
+
+impl<K: Atom, C> Clone for Action<K, C> {
+    fn clone(&self) -> Action<K, C> {
+        Action {
+            name: self.name.clone(),
+            cost: self.cost,
+            pre_conditions: self.pre_conditions.clone(),
+            post_conditions: self.post_conditions.clone(),
+            relational_pre_conditions: self.relational_pre_conditions.clone(),
+            any_of: self.any_of.clone(),
+            once: self.once,
+            group: self.group.clone(),
+            preference: self.preference,
+            description: self.description.clone(),
+            tags: self.tags.clone(),
+            context_check: self.context_check.clone(),
+            cost_fn: self.cost_fn.clone(),
+        }
+    }
+}
+
+impl<K: Atom + fmt::Debug, C> fmt::Debug for Action<K, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Action")
+            .field("name", &self.name)
+            .field("cost", &self.cost)
+            .field("pre_conditions", &self.pre_conditions)
+            .field("post_conditions", &self.post_conditions)
+            .field("relational_pre_conditions", &self.relational_pre_conditions)
+            .field("any_of", &self.any_of)
+            .field("once", &self.once)
+            .field("group", &self.group)
+            .field("preference", &self.preference)
+            .field("description", &self.description)
+            .field("tags", &self.tags)
+            .field("context_check", &self.context_check.as_ref().map(|_| "Fn(&C) -> bool"))
+            .field("cost_fn", &self.cost_fn.as_ref().map(|_| "Fn(&State<K>, &C) -> usize"))
+            .finish()
+    }
+}
+
+/// The authoring mistakes that can be found in an action.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum ActionError<K = String> {
+    /// The action has no post-conditions, so it can never make progress.
+    NoPostConditions,
+    /// The effect on this atom leaves it as it was required to be before applying the action.
+    RedundantEffect(K),
+    /// The action costs nothing, so plans can go back and forth through the states its effects
+    /// lead to for free, and its relative effects can lead to infinitely many states for free.
+    ZeroCost,
+}
+
+impl<K: fmt::Display> fmt::Display for ActionError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ActionError::NoPostConditions => write!(f, "the action has no post-conditions"),
+            ActionError::RedundantEffect(ref atom) => {
+                write!(f, "the effect on `{}` doesn't change anything", atom)
+            }
+            ActionError::ZeroCost => write!(f, "the action costs nothing"),
+        }
+    }
+}
+
+impl<K: fmt::Debug + fmt::Display> Error for ActionError<K> {}
+
+/// The reasons why libraries of actions couldn't be merged, as returned by `merge_actions`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum MergeError {
+    /// Several different actions have this name, so plans couldn't tell them apart by name.
+    Duplicate(String),
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MergeError::Duplicate(ref name) => {
+                write!(f, "several different actions are named `{}`", name)
+            }
+        }
+    }
+}
+
+impl Error for MergeError {}
+
+/// Merges libraries of actions into one, in order, making sure that no two actions have the same
+/// name.
+///
+/// Actions identical to one merged before are left out, as when libraries share the actions of a
+/// common one. Actions with a context check or a dynamic cost never are, as their functions can't
+/// be compared.
+pub fn merge_actions<K: Atom, C>(libraries: &[&[Action<K, C>]])
+                                 -> Result<Vec<Action<K, C>>, MergeError> {
+    let mut merged: Vec<Action<K, C>> = vec![];
+    for action in libraries.iter().flat_map(|library| library.iter()) {
+        match merged.iter().find(|other| other.name == action.name) {
+            Some(other) if other == action && action.context_check.is_none() &&
+                           action.cost_fn.is_none() => {}
+            Some(_) => return Err(MergeError::Duplicate(action.name.clone())),
+            None => merged.push(action.clone()),
+        }
+    }
+
+    Ok(merged)
+}
+
+/// A plan, made of the actions to apply in order, along with their cost.
+///
+/// Plans are displayed as the names of their actions joined by arrows, followed by their cost, as
+/// in `walk_to_dog -> pet_dog -> dog_wiggles_tail (cost 3)`.
+#[derive(PartialEq, Eq, Clone)]
+pub struct Plan<'a, K: 'a + Atom = String> {
+    pub actions: Vec<&'a Action<K>>,
+    /// The cost of each action, in the state it's applied to.
+    pub step_costs: Vec<usize>,
+    pub cost: usize,
+}
+
+impl<'a, K: Atom> Plan<'a, K> {
+    /// Returns the names of the actions of this plan, in order.
+    pub fn names(&self) -> Vec<String> {
+        self.actions.iter().map(|action| action.name.clone()).collect()
+    }
+
+    /// Returns the actions of this plan, in order, along with the cost each of them had in the
+    /// state it was applied to, which differs from its static cost when it has a cost function.
+    pub fn steps(&self) -> Vec<(&'a Action<K>, usize)> {
+        self.actions.iter().cloned().zip(self.step_costs.iter().cloned()).collect()
+    }
+
+    /// Returns a record of this plan, which refers to its actions by name.
+    pub fn to_record(&self) -> PlanRecord {
+        let mut total_cost = 0;
+        let steps = self.actions
+            .iter()
+            .zip(&self.step_costs)
+            .map(|(action, &cost)| {
+                total_cost += cost;
+                PlanStep {
+                    name: action.name.clone(),
+                    cost,
+                    total_cost,
+                }
+            })
+            .collect();
+
+        PlanRecord {
+            steps,
+            cost: self.cost,
+        }
+    }
+}
+
+impl<'a, K: Atom> fmt::Display for Plan<'a, K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.actions.is_empty() {
+            write!(f, "no actions")?;
+        } else {
+            write!(f, "{}", self.names().join(" -> "))?;
+        }
+        write!(f, " (cost {})", self.cost)
+    }
+}
+
+impl<'a, K: Atom> fmt::Debug for Plan<'a, K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// A plan which refers to its actions by name, so that it can outlive them, or be serialized.
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct PlanRecord {
+    pub steps: Vec<PlanStep>,
+    pub cost: usize,
+}
+
+/// An action of a `PlanRecord`.
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct PlanStep {
+    pub name: String,
+    /// The cost of the action, in the state it's applied to.
+    pub cost: usize,
+    /// The cost of the plan up to and including the action.
+    pub total_cost: usize,
+}
+
+/// An event of the search for a plan, as reported by `plan_with_trace`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum TraceEvent<'a, K = String> {
+    /// A node of the search is expanded, and `Applied` events follow for each of its successors.
+    Expanded {
+        /// The state of the world at this node.
+        state: State<K>,
+        /// The cost paid to get to this node from the initial state.
+        g_cost: usize,
+        /// The estimated cost left to pay to get to the goal from this node.
+        h_cost: usize,
+    },
+    /// An action is applied to the state of the node being expanded.
+    Applied { action_name: &'a str },
+}
+
+/// The reasons why a plan couldn't be formulated.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum PlanError<K = String> {
+    /// No sequence of the allowed actions leads to the goal state.
+    Unreachable,
+    /// A goal state atom is neither in the initial state nor in any action's post-conditions.
+    GoalAtomNeverProduced(K),
+    /// The set of allowed actions is empty, while the initial state doesn't reach the goal state.
+    EmptyActionSet,
+    /// The search expanded as many nodes as it was allowed to without finding a plan.
+    NodeLimitExceeded,
+    /// The named action failed its validation.
+    InvalidAction(String, ActionError<K>),
+    /// Several of the allowed actions have this name, so plans can't tell them apart by name.
+    DuplicateActionName(String),
+    /// The search ran for as long as it was allowed to without finding a plan.
+    Timeout,
+    /// A goal atom doesn't have its goal value in the initial state, and no action can change it.
+    ImmutableGoalAtom(K),
+}
+
+impl<K: fmt::Display> fmt::Display for PlanError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PlanError::Unreachable => write!(f, "the goal state is unreachable"),
+            PlanError::GoalAtomNeverProduced(ref atom) => {
+                write!(f, "the goal atom `{}` is never produced", atom)
+            }
+            PlanError::EmptyActionSet => write!(f, "the set of allowed actions is empty"),
+            PlanError::NodeLimitExceeded => write!(f, "the search node limit was exceeded"),
+            PlanError::InvalidAction(ref name, ref error) => {
+                write!(f, "the action `{}` is invalid: {}", name, error)
+            }
+            PlanError::DuplicateActionName(ref name) => {
+                write!(f, "several actions are named `{}`", name)
+            }
+            PlanError::Timeout => write!(f, "the search timed out"),
+            PlanError::ImmutableGoalAtom(ref atom) => {
+                write!(f, "the goal atom `{}` can't be changed to its goal value", atom)
+            }
+        }
+    }
+}
+
+impl<K: fmt::Debug + fmt::Display> Error for PlanError<K> {}
+
+/// Formulates a plan to get from an initial state to a goal state using a set of allowed actions.
+///
+/// The search is guided by the number of goal atoms which don't have their goal value yet. The plan
+/// is optimal as long as no action fixes more goal atoms than its cost at once, since this estimate
+/// then never exceeds the actual cost left to pay. Relative effects keep it that way: an integer
+/// atom counts as a single mismatch however far it is from its goal value, so the actions bringing
+/// it there cost at least as much as estimated. However, they can make the number of reachable
+/// states infinite, in which case an unreachable goal makes the search run forever; use
+/// `plan_with_limits` to bound it.
+///
+/// The same inputs always lead to the same plan, from one run to the next: states are only hashed
+/// to be looked up, and the search breaks ties between them in the order it reaches them in.
+pub fn plan<'a, G, K>(initial_state: &'a State<K>,
+                      goal_state: &G,
+                      allowed_actions: &'a [Action<K>])
+                      -> Option<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    plan_with_cost(initial_state, goal_state, allowed_actions).map(|plan| plan.actions)
+}
+
+/// Formulates a plan like `plan` does, and returns it as a `Plan`, which also holds the total cost
+/// of the planned actions.
+///
+/// The cost is zero when the goal state is already reached in the initial state.
+pub fn plan_with_cost<'a, G, K>(initial_state: &'a State<K>,
+                                goal_state: &G,
+                                allowed_actions: &'a [Action<K>])
+                                -> Option<Plan<'a, K>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    search_plan(initial_state, goal_state, allowed_actions)
+}
+
+/// Formulates a plan like `plan` does, taking the initial state and the goal state by value, so
+/// that they don't have to be kept around for as long as the plan.
+pub fn plan_owned_states<K: Atom>(initial_state: State<K>,
+                                  goal_state: State<K>,
+                                  allowed_actions: &[Action<K>])
+                                  -> Option<Vec<&Action<K>>> {
+    search_plan(&initial_state, &goal_state, allowed_actions).map(|plan| plan.actions)
+}
+
+/// Formulates a plan like `plan_with_cost` does, where the plan doesn't borrow the initial state.
+fn search_plan<'a, G, K>(initial_state: &State<K>,
+                         goal_state: &G,
+                         allowed_actions: &'a [Action<K>])
+                         -> Option<Plan<'a, K>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+    if space.immutable_goal_atom(&goal).is_some() {
+        return None;
+    }
+
+    space.search(|node| node.mismatch_count(&goal), |node| node.matches(&goal))
+        .map(|(path, cost)| {
+            let (indices, step_costs): (Vec<_>, _) = path_steps(&path).into_iter().unzip();
+            Plan {
+                actions: indices.into_iter().map(|index| &allowed_actions[index]).collect(),
+                step_costs,
+                cost,
+            }
+        })
+}
+
+/// Formulates a plan like `plan` does, and returns copies of the planned actions, so that the plan
+/// can outlive the set of allowed actions.
+pub fn plan_owned<G, K>(initial_state: &State<K>,
+                        goal_state: &G,
+                        allowed_actions: &[Action<K>])
+                        -> Option<Vec<Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    plan(initial_state, goal_state, allowed_actions)
+        .map(|actions| actions.into_iter().cloned().collect())
+}
+
+/// Formulates a plan like `plan` does, and returns the indices of the planned actions in the set of
+/// allowed actions, so that the plan doesn't borrow them.
+pub fn plan_indices<G, K>(initial_state: &State<K>,
+                          goal_state: &G,
+                          allowed_actions: &[Action<K>])
+                          -> Option<Vec<usize>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+    if space.immutable_goal_atom(&goal).is_some() {
+        return None;
+    }
+
+    space.search(|node| node.mismatch_count(&goal), |node| node.matches(&goal))
+        .map(|(path, _)| path_action_indices(&path))
+}
+
+/// Formulates a plan like `plan` does, and returns it along with the state it leads to.
+///
+/// The final state has the goal values of the goal atoms, but also the values of all the other
+/// atoms once every planned action has been applied, which may fulfill more than the goal asks.
+pub fn plan_with_final_state<'a, G, K>(initial_state: &State<K>,
+                                       goal_state: &G,
+                                       allowed_actions: &'a [Action<K>])
+                                       -> Option<(Vec<&'a Action<K>>, State<K>)>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+    if space.immutable_goal_atom(&goal).is_some() {
+        return None;
+    }
+
+    space.search(|node| node.mismatch_count(&goal), |node| node.matches(&goal))
+        .map(|(path, _)| {
+            let final_state = path[path.len() - 1].current_state();
+            (path_actions(&path, allowed_actions), final_state)
+        })
+}
+
+/// Formulates a plan like `plan_owned` does, from a set of allowed actions shared between threads.
+///
+/// Planning only ever reads from the actions, so any number of threads can plan concurrently with
+/// clones of the same `Arc`.
+pub fn plan_arc<G, K>(initial_state: &State<K>,
+                      goal_state: &G,
+                      allowed_actions: Arc<[Action<K>]>)
+                      -> Option<Vec<Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    plan_owned(initial_state, goal_state, &allowed_actions)
+}
+
+/// Formulates a plan like `plan` does, and pairs each planned action with the state of the world
+/// right after it has been applied.
+///
+/// The initial state isn't part of the returned list, so a goal state already reached in the
+/// initial state gives an empty list.
+pub fn plan_with_states<'a, G, K>(initial_state: &'a State<K>,
+                                  goal_state: &G,
+                                  allowed_actions: &'a [Action<K>])
+                                  -> Option<Vec<(&'a Action<K>, State<K>)>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    space.search(|node| node.mismatch_count(&goal), |node| node.matches(&goal)).map(|(path, _)| {
+        let states = path.iter().skip(1).map(|node| node.current_state());
+        path_actions(&path, allowed_actions).into_iter().zip(states).collect()
+    })
+}
+
+/// Formulates a plan like `plan` does, and pairs each planned action with the positions in the
+/// plan of the earlier actions it depends on, so that actions which don't depend on each other can
+/// be applied concurrently.
+///
+/// An action depends on the last earlier action changing an atom it reads, through its conditions
+/// or its relative effects. It also depends on the earlier actions it would get in the way of if
+/// applied first, which are the last earlier action changing an atom it changes too, and the
+/// actions reading that atom since. Applying each action once all of those it depends on have been
+/// applied leads to the same state as applying the plan in order.
+pub fn plan_partial_order<'a, G, K>(initial_state: &'a State<K>,
+                                    goal_state: &G,
+                                    allowed_actions: &'a [Action<K>])
+                                    -> Option<Vec<(&'a Action<K>, Vec<usize>)>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let actions = plan(initial_state, goal_state, allowed_actions)?;
+
+    // The last action changing each atom so far, and the actions reading it since.
+    let mut last_changes: BTreeMap<&K, usize> = BTreeMap::new();
+    let mut reads_since: BTreeMap<&K, Vec<usize>> = BTreeMap::new();
+    let mut steps = vec![];
+    for (position, action) in actions.into_iter().enumerate() {
+        let mut read_atoms: Vec<&K> = action.pre_conditions.keys().collect();
+        for pre in &action.relational_pre_conditions {
+            read_atoms.push(&pre.left);
+            read_atoms.push(&pre.right);
+        }
+        read_atoms.extend(action.any_of.iter().flat_map(|alternative| alternative.keys()));
+        for (name, effect) in &action.post_conditions {
+            match *effect {
+                Effect::Set(_) => {}
+                Effect::Add(_) | Effect::Toggle => read_atoms.push(name),
+            }
+        }
+
+        let mut dependencies = BTreeSet::new();
+        for &name in &read_atoms {
+            dependencies.extend(last_changes.get(name));
+        }
+        for name in action.post_conditions.keys() {
+            dependencies.extend(last_changes.get(name));
+            dependencies.extend(reads_since.get(name).into_iter().flatten());
+        }
+
+        for &name in &read_atoms {
+            reads_since.entry(name).or_default().push(position);
+        }
+        for name in action.post_conditions.keys() {
+            last_changes.insert(name, position);
+            reads_since.remove(name);
+        }
+        steps.push((action, dependencies.into_iter().collect()));
+    }
+
+    Some(steps)
+}
+
+/// Formulates all the plans sharing the optimal cost to get from an initial state to a goal state.
+///
+/// The plans are sorted by the names of their actions, and plans with the same names by the
+/// positions of their actions in the set of allowed actions, so that their order is always the
+/// same.
+#[cfg(feature = "std")]
+pub fn plan_all_optimal<'a, G, K>(initial_state: &'a State<K>,
+                                  goal_state: &G,
+                                  allowed_actions: &'a [Action<K>])
+                                  -> Vec<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    let mut plans: Vec<Vec<usize>> =
+        match space.search_all(|node| node.mismatch_count(&goal), |node| node.matches(&goal)) {
+            Some((paths, _)) => paths.iter().flat_map(|path| path_index_sequences(path)).collect(),
+            None => vec![],
+        };
+
+    // Plans with the same names are told apart by the positions of their actions.
+    plans.sort_by(|a, b| {
+        let names = |plan: &Vec<usize>| -> Vec<&String> {
+            plan.iter().map(|&index| &allowed_actions[index].name).collect()
+        };
+        names(a).cmp(&names(b)).then_with(|| a.cmp(b))
+    });
+    plans.into_iter()
+        .map(|plan| plan.into_iter().map(|index| &allowed_actions[index]).collect())
+        .collect()
+}
+
+/// How to choose between plans sharing the optimal cost, as done by `plan_with_tiebreak`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg(feature = "std")]
+pub enum TieBreak {
+    /// Prefers the plans with the fewest actions, then the first one by the names of its actions.
+    FewerActions,
+    /// Prefers the first plan by the names of its actions.
+    Lexicographic,
+    /// Prefers the plans whose actions have the highest sum of preferences, then the first one by
+    /// the names of its actions.
+    Preferred,
+    /// Picks one of the plans from a seed, so that different seeds can lead to different plans,
+    /// while the same seed always leads to the same plan, from one run to the next.
+    Seeded(u64),
+}
+
+/// Formulates a plan like `plan` does, and chooses between the plans sharing the optimal cost as
+/// told by `tie_break`, so that the same inputs always lead to the same plan.
+#[cfg(feature = "std")]
+pub fn plan_with_tiebreak<'a, G, K>(initial_state: &'a State<K>,
+                                    goal_state: &G,
+                                    allowed_actions: &'a [Action<K>],
+                                    tie_break: TieBreak)
+                                    -> Option<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    // The optimal plans come sorted by the names of their actions.
+    let plans = plan_all_optimal(initial_state, goal_state, allowed_actions);
+    match tie_break {
+        TieBreak::FewerActions => plans.into_iter().min_by_key(|plan| plan.len()),
+        TieBreak::Lexicographic => plans.into_iter().next(),
+        TieBreak::Preferred => {
+            plans.into_iter().min_by_key(|plan| {
+                Reverse(plan.iter().map(|action| i64::from(action.preference)).sum::<i64>())
+            })
+        }
+        TieBreak::Seeded(seed) if !plans.is_empty() => {
+            // The seed is scrambled, so that close seeds lead to unrelated plans.
+            let index = Rng(seed).next() % plans.len() as u64;
+            plans.into_iter().nth(index as usize)
+        }
+        TieBreak::Seeded(_) => None,
+    }
+}
+
+/// Which nodes to expand first among those with the same estimated total cost, as done by
+/// `plan_with_tie_bias`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg(feature = "std")]
+pub enum TieBias {
+    /// Prefers the nodes with the least estimated cost left, which are the closest to the goal.
+    PreferGoal,
+    /// Prefers the nodes with the most estimated cost left, which are the closest to the start.
+    PreferStart,
+}
+
+/// Formulates a plan like `plan` does, expanding the nodes sharing the same estimated total cost
+/// in the order told by `bias`.
+///
+/// The plan found costs the same either way, but the number of nodes expanded on the way can be
+/// very different when many nodes share the same estimated total cost. Preferring the goal then
+/// tends to expand fewer nodes, and is what `plan` does.
+#[cfg(feature = "std")]
+pub fn plan_with_tie_bias<'a, G, K>(initial_state: &'a State<K>,
+                                    goal_state: &G,
+                                    allowed_actions: &'a [Action<K>],
+                                    bias: TieBias)
+                                    -> Option<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    space.search_biased(bias,
+                        |node| node.possible_next_nodes(),
+                        |node| node.mismatch_count(&goal),
+                        |node| node.matches(&goal))
+        .map(|(path, _)| path_actions(&path, allowed_actions))
+}
+
+/// Formulates a plan like `plan` does, with actions that are only applicable when their context
+/// check passes for the given context, and whose cost functions can depend on it.
+///
+/// The context is only read, and stays the same for the whole search.
+pub fn plan_in_context<'a, G, K, C>(initial_state: &'a State<K>,
+                                    goal_state: &G,
+                                    allowed_actions: &'a [Action<K, C>],
+                                    context: &C)
+                                    -> Option<Vec<&'a Action<K, C>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, context);
+    let goal = space.goal(goal_state);
+
+    space.search(|node| node.mismatch_count(&goal), |node| node.matches(&goal))
+        .map(|(path, _)| path_actions(&path, allowed_actions))
+}
+
+/// Formulates a plan to get from an initial state to whichever of the goal states is the cheapest
+/// to reach, and returns it along with the index of that goal state.
+///
+/// When several goal states are reached by the plan, the index of the first one is returned.
+pub fn plan_any<'a, G, K>(initial_state: &'a State<K>,
+                          goal_states: &[G],
+                          allowed_actions: &'a [Action<K>])
+                          -> Option<(Vec<&'a Action<K>>, usize)>
+    where G: ToGoal<K>,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goals: Vec<_> = goal_states.iter().map(|goal_state| space.goal(goal_state)).collect();
+
+    // The smallest of the estimates to reach each goal never overestimates reaching the nearest.
+    let result = space.search(|node| {
+                                  goals.iter()
+                                      .map(|goal| node.mismatch_count(goal))
+                                      .min()
+                                      .unwrap_or(0)
+                              },
+                              |node| goals.iter().any(|goal| node.matches(goal)));
+
+    result.map(|(path, _)| {
+        let index = goals.iter().position(|goal| path.last().unwrap().matches(goal)).unwrap();
+        (path_actions(&path, allowed_actions), index)
+    })
+}
+
+/// Formulates a plan to get from an initial state to each of the goal states, in the same order,
+/// sharing the search between all of them.
+///
+/// States are explored in order of increasing cost from the initial state, and the first time a
+/// goal state is reached gives the cheapest plan to it. The search stops once every goal state
+/// that can be reached is, so getting to many goal states costs about as much as getting to the
+/// farthest one. Unlike `plan`, the search doesn't head toward any goal state, which makes it
+/// slower than `plan` for a single goal state.
+pub fn plan_batch<'a, G, K>(initial_state: &'a State<K>,
+                            goal_states: &[G],
+                            allowed_actions: &'a [Action<K>])
+                            -> Vec<Option<Vec<&'a Action<K>>>>
+    where G: ToGoal<K>,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goals: Vec<_> = goal_states.iter().map(|goal_state| space.goal(goal_state)).collect();
+
+    // Goal states that can never be reached are left out right away, so as not to wait for them.
+    let mut plans = vec![None; goals.len()];
+    let mut is_pending: Vec<bool> =
+        goals.iter().map(|goal| space.immutable_goal_atom(goal).is_none()).collect();
+    let mut pending_count = is_pending.iter().filter(|&&pending| pending).count();
+
+    let mut search = space.by_cost(usize::MAX);
+    while let Some((node, _)) = search.next() {
+        for (goal_index, goal) in goals.iter().enumerate() {
+            if is_pending[goal_index] && node.matches(goal) {
+                is_pending[goal_index] = false;
+                pending_count -= 1;
+                let actions = search.action_indices()
+                    .into_iter()
+                    .map(|index| &allowed_actions[index])
+                    .collect();
+                plans[goal_index] = Some(actions);
+            }
+        }
+        if pending_count == 0 {
+            break;
+        }
+    }
+
+    plans
+}
+
+/// Formulates a plan like `plan` does, but inflates the search heuristic by the given weight, which
+/// is expected to be greater than or equal to `1.0`.
+///
+/// The greater the weight, the more eagerly the search heads toward the goal state, which makes it
+/// faster, at the expense of optimality: as long as `plan` would be optimal, the cost of the plan
+/// found is at most `weight` times the optimal cost. A weight of `1.0` behaves exactly like `plan`.
+pub fn plan_weighted<'a, G, K>(initial_state: &'a State<K>,
+                               goal_state: &G,
+                               allowed_actions: &'a [Action<K>],
+                               weight: f64)
+                               -> Option<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    space.search(|node| (node.mismatch_count(&goal) as f64 * weight) as usize,
+                 |node| node.matches(&goal))
+        .map(|(path, _)| path_actions(&path, allowed_actions))
+}
+
+/// Formulates a plan like `plan` does, but weighs each goal atom which doesn't have its goal value
+/// yet by its weight in `weights` when guiding the search, instead of counting it as 1.
+///
+/// Atoms with no listed weight weigh 1. Weighing the atoms that are the most expensive to fix
+/// more makes the search head more directly toward the goal state. The plan stays optimal as long
+/// as the sum of the weights of the mismatched atoms never exceeds the actual cost left to pay.
+pub fn plan_with_weights<'a, G, K>(initial_state: &'a State<K>,
+                                   goal_state: &G,
+                                   allowed_actions: &'a [Action<K>],
+                                   weights: &BTreeMap<K, usize>)
+                                   -> Option<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+    let goal_weights = space.goal_weights(&goal, weights);
+
+    space.search(|node| node.weighted_mismatch_count(&goal, &goal_weights),
+                 |node| node.matches(&goal))
+        .map(|(path, _)| path_actions(&path, allowed_actions))
+}
+
+/// Formulates a plan like `plan` does, which never goes through any of the forbidden states.
+///
+/// A state is forbidden when all the atoms of one of the `forbidden` states have the same values
+/// in it, even if only until the next action. The initial state is never considered forbidden,
+/// given that the world is already in it.
+pub fn plan_with_forbidden<'a, G, K>(initial_state: &'a State<K>,
+                                     goal_state: &G,
+                                     allowed_actions: &'a [Action<K>],
+                                     forbidden: &[State<K>])
+                                     -> Option<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+    let forbidden: Vec<_> = forbidden.iter().map(|state| space.goal(state)).collect();
+
+    space.search_with(|node| {
+                          let mut successors = node.possible_next_nodes();
+                          successors.retain(|successor| {
+                              !forbidden.iter().any(|state| successor.0.matches(state))
+                          });
+                          successors
+                      },
+                      |node| node.mismatch_count(&goal),
+                      |node| node.matches(&goal))
+        .map(|(path, _)| path_actions(&path, allowed_actions))
+}
+
+/// Formulates a plan like `plan` does, which only goes through states where all the atoms of the
+/// `invariants` state have the same values, even if only until the next action.
+///
+/// Unlike forbidden states, the initial state must meet the invariants too, otherwise there's no
+/// plan at all.
+pub fn plan_with_invariants<'a, G, K>(initial_state: &'a State<K>,
+                                      goal_state: &G,
+                                      allowed_actions: &'a [Action<K>],
+                                      invariants: &State<K>)
+                                      -> Option<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+    let invariants = space.goal(invariants);
+    if !space.initial_node().matches(&invariants) {
+        return None;
+    }
+
+    space.search_with(|node| {
+                          let mut successors = node.possible_next_nodes();
+                          successors.retain(|successor| successor.0.matches(&invariants));
+                          successors
+                      },
+                      |node| node.mismatch_count(&goal),
+                      |node| node.matches(&goal))
+        .map(|(path, _)| path_actions(&path, allowed_actions))
+}
+
+/// Formulates the cheapest plan made of at most `max_len` actions, or returns `None` if there's no
+/// such plan, even if there are longer ones.
+#[cfg(feature = "std")]
+pub fn plan_with_max_len<'a, G, K>(initial_state: &'a State<K>,
+                                   goal_state: &G,
+                                   allowed_actions: &'a [Action<K>],
+                                   max_len: usize)
+                                   -> Option<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    space.search_bounded(max_len,
+                        |node| node.mismatch_count(&goal),
+                        |node| node.matches(&goal))
+        .map(|(path, _)| path_actions(&path, allowed_actions))
+}
+
+/// Formulates the cheapest plan costing at most `max_cost`, or returns `None` if the cheapest plan
+/// costs more.
+///
+/// Unlike `plan_weighted`, which trades the cost of the plan for a quicker search, this is a hard
+/// limit: the nodes which can only be reached for more than `max_cost` are never expanded.
+pub fn plan_within_budget<'a, G, K>(initial_state: &'a State<K>,
+                                    goal_state: &G,
+                                    allowed_actions: &'a [Action<K>],
+                                    max_cost: usize)
+                                    -> Option<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    space.search_with_costs(|node, g_cost, _| {
+                                let mut successors = node.possible_next_nodes();
+                                successors.retain(|&(_, cost)| g_cost + cost <= max_cost);
+                                successors
+                            },
+                            |node| node.mismatch_count(&goal),
+                            |node| node.matches(&goal))
+        .map(|(path, _)| path_actions(&path, allowed_actions))
+}
+
+/// Formulates a plan like `plan` does, with the costs in `overrides` instead of those of the
+/// actions with the same names, so that costs can be tuned for a single plan without changing the
+/// actions, which may be shared.
+///
+/// Actions with no cost listed keep their static cost or their cost function.
+pub fn plan_with_cost_overrides<'a, G, K>(initial_state: &'a State<K>,
+                                          goal_state: &G,
+                                          allowed_actions: &'a [Action<K>],
+                                          overrides: &BTreeMap<String, usize>)
+                                          -> Option<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+    space.override_costs(|index| overrides.get(&allowed_actions[index].name).cloned());
+
+    space.search(|node| node.mismatch_count(&goal), |node| node.matches(&goal))
+        .map(|(path, _)| path_actions(&path, allowed_actions))
+}
+
+/// Formulates a plan like `plan` does, with fractional costs, and returns it along with its cost.
+///
+/// The cost of each action is given by `cost`, from the action and the state it's applied to,
+/// instead of the static cost and cost function of the action, so that costs such as distances
+/// don't need to be scaled to integers. Costs must be positive numbers. The plan is optimal under
+/// the same conditions as `plan`.
+#[cfg(feature = "std")]
+pub fn plan_with_real_costs<'a, F, G, K>(initial_state: &'a State<K>,
+                                         goal_state: &G,
+                                         allowed_actions: &'a [Action<K>],
+                                         mut cost: F)
+                                         -> Option<(Vec<&'a Action<K>>, f64)>
+    where G: ToGoal<K> + ?Sized,
+          F: FnMut(&Action<K>, &State<K>) -> f64,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    space.search_real(|index, state| cost(&allowed_actions[index], state),
+                      |node| node.mismatch_count(&goal) as f64,
+                      |node| node.matches(&goal))
+        .map(|(indices, total_cost)| {
+            (indices.into_iter().map(|index| &allowed_actions[index]).collect(), total_cost)
+        })
+}
+
+/// Formulates a plan like `plan` does, but guides the search with a custom heuristic.
+///
+/// The heuristic is given the current state and the goal, and estimates the cost left to pay
+/// to get from one to the other. The plan is only optimal if the heuristic is admissible, which is
+/// to say that it never overestimates that cost.
+pub fn plan_with_heuristic<'a, F, G, K>(initial_state: &'a State<K>,
+                                        goal_state: &G,
+                                        allowed_actions: &'a [Action<K>],
+                                        mut heuristic: F)
+                                        -> Option<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          F: FnMut(&State<K>, &G) -> usize,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    space.search(|node| heuristic(&node.current_state(), goal_state),
+                 |node| node.matches(&goal))
+        .map(|(path, _)| path_actions(&path, allowed_actions))
+}
+
+/// Formulates a plan to get from an initial state to any state for which `is_goal` is `true`,
+/// guiding the search with a custom heuristic, so that goals don't have to be a set of atoms.
+///
+/// Such goals can be anything about a state, such as having at least 3 of 5 flags set. The
+/// heuristic estimates the cost left to pay to get from a state to the goal. As with
+/// `plan_with_heuristic`, the plan is only optimal if the heuristic never overestimates that cost,
+/// and a heuristic estimating it as zero makes for an optimal plan, at the cost of exploring more
+/// states. Both functions are given a full copy of the state of each node they're called for.
+pub fn plan_until<'a, FG, FH, K>(initial_state: &'a State<K>,
+                                 mut is_goal: FG,
+                                 allowed_actions: &'a [Action<K>],
+                                 mut heuristic: FH)
+                                 -> Option<Vec<&'a Action<K>>>
+    where FG: FnMut(&State<K>) -> bool,
+          FH: FnMut(&State<K>) -> usize,
+          K: Atom
+{
+    let space = SearchSpace::new(initial_state, allowed_actions, &());
+
+    space.search(|node| heuristic(&node.current_state()),
+                 |node| is_goal(&node.current_state()))
+        .map(|(path, _)| path_actions(&path, allowed_actions))
+}
+
+/// Formulates a plan achieving each of the given subgoals in turn, by planning from the initial
+/// state to the first subgoal, then from the state this leads to, to the next one, and so on.
+///
+/// Each leg is planned like `plan` does, so the whole plan is only the cheapest one for each leg
+/// taken separately. When a leg can't be planned, the index of its subgoal is returned.
+pub fn plan_sequence<'a, G, K>(initial_state: &State<K>,
+                               subgoals: &[G],
+                               allowed_actions: &'a [Action<K>])
+                               -> Result<Vec<&'a Action<K>>, usize>
+    where G: ToGoal<K>,
+          K: Atom
+{
+    let mut state = initial_state.clone();
+    let mut planned = vec![];
+    for (index, subgoal) in subgoals.iter().enumerate() {
+        for action_index in plan_indices(&state, subgoal, allowed_actions).ok_or(index)? {
+            let action = &allowed_actions[action_index];
+            apply(action, &mut state);
+            planned.push(action);
+        }
+    }
+
+    Ok(planned)
+}
+
+/// Formulates a plan like `plan` does, with a heuristic estimating every cost left as zero, which
+/// makes the search a plain Dijkstra search, and returns it as a `Plan`.
+///
+/// This explores more states than `plan`, but doesn't depend on the heuristic in any way, so the
+/// plan is always among the cheapest ones, which makes it a reference to check other plans against.
+pub fn plan_dijkstra<'a, G, K>(initial_state: &'a State<K>,
+                               goal_state: &G,
+                               allowed_actions: &'a [Action<K>])
+                               -> Option<Plan<'a, K>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    space.search(|_| 0, |node| node.matches(&goal))
+        .map(|(path, cost)| {
+            let (indices, step_costs): (Vec<_>, _) = path_steps(&path).into_iter().unzip();
+            Plan {
+                actions: indices.into_iter().map(|index| &allowed_actions[index]).collect(),
+                step_costs,
+                cost,
+            }
+        })
+}
+
+/// Formulates a plan like `plan` does, searching backward from the goal state instead of forward
+/// from the initial state.
+///
+/// The search starts from the goal atoms, and replaces those that an action produces with the
+/// pre-conditions of the action, until the initial state fulfills all the atoms left. This explores
+/// fewer states than `plan` when the goal state only has a few atoms of a large world, as only the
+/// actions helping toward the goal are considered. However, only actions with equality
+/// pre-conditions and absolute effects, which can be applied any number of times and aren't part
+/// of a group, can be planned this way, and cost functions are ignored in favor of the static cost
+/// of the actions, as the state an action is applied to isn't known while searching backward.
+#[cfg(feature = "std")]
+pub fn plan_backward<'a, G, K>(initial_state: &'a State<K>,
+                               goal_state: &G,
+                               allowed_actions: &'a [Action<K>])
+                               -> Option<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    space.search_backward(&goal)
+        .map(|(indices, _)| indices.into_iter().map(|index| &allowed_actions[index]).collect())
+}
+
+/// Formulates a plan like `plan` does, using iterative-deepening A* instead of A*.
+///
+/// Only the states along the path being explored are kept in memory, instead of every state
+/// reached so far, at the expense of exploring some states again and again. The plan is optimal
+/// under the same conditions as `plan`.
+#[cfg(feature = "std")]
+pub fn plan_ida_star<'a, G, K>(initial_state: &'a State<K>,
+                               goal_state: &G,
+                               allowed_actions: &'a [Action<K>])
+                               -> Option<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    space.search_ida(|node| node.mismatch_count(&goal), |node| node.matches(&goal))
+        .map(|(path, _)| path_actions(&path, allowed_actions))
+}
+
+/// Formulates a plan to fulfill all the hard goal atoms of a goal spec like `plan` does, which
+/// fulfills as many of its soft goal atoms as possible among the cheapest plans.
+///
+/// Soft goal atoms never make a plan more expensive: a cheaper plan fulfilling none of them is
+/// preferred over a more expensive one fulfilling them all. Among plans fulfilling as many, the
+/// first one by the names of its actions is chosen.
+#[cfg(feature = "std")]
+pub fn plan_with_spec<'a, K>(initial_state: &'a State<K>,
+                             goal_spec: &GoalSpec<K>,
+                             allowed_actions: &'a [Action<K>])
+                             -> Option<Vec<&'a Action<K>>>
+    where K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(&goal_spec.hard);
+    let soft_goal = space.goal(&goal_spec.soft);
+
+    let (paths, _) = space.search_all(|node| node.mismatch_count(&goal),
+                                      |node| node.matches(&goal))?;
+    paths.iter()
+        .map(|path| {
+            let mismatch_count = path[path.len() - 1].mismatch_count(&soft_goal);
+            (mismatch_count, path_actions(path, allowed_actions))
+        })
+        .min_by(|a, b| {
+            a.0.cmp(&b.0).then_with(|| {
+                a.1.iter().map(|action| &action.name).cmp(b.1.iter().map(|action| &action.name))
+            })
+        })
+        .map(|(_, actions)| actions)
+}
+
+/// Formulates a plan like `plan` does, and reports what the search does along the way to `trace`.
+///
+/// Each node expanded by the search gives a `TraceEvent::Expanded` event, followed by an
+/// `TraceEvent::Applied` event for each action applicable to it.
+pub fn plan_with_trace<'a, F, G, K>(initial_state: &'a State<K>,
+                                    goal_state: &G,
+                                    allowed_actions: &'a [Action<K>],
+                                    mut trace: F)
+                                    -> Option<Vec<&'a Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          F: FnMut(TraceEvent<'a, K>),
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    let result = space.search_with_costs(|node, g_cost, _| {
+                                             trace(TraceEvent::Expanded {
+                                                 state: node.current_state(),
+                                                 g_cost,
+                                                 h_cost: node.mismatch_count(&goal),
+                                             });
+
+                                             let mut successors = vec![];
+                                             for (index, child, cost) in node.transitions() {
+                                                 let action_name = &allowed_actions[index].name;
+                                                 trace(TraceEvent::Applied { action_name });
+                                                 successors.push((child, cost));
+                                             }
+                                             successors
+                                         },
+                                         |node| node.mismatch_count(&goal),
+                                         |node| node.matches(&goal));
+
+    result.map(|(path, _)| path_actions(&path, allowed_actions))
+}
+
+/// Statistics about the search for a plan, as returned by `plan_with_stats`.
+#[cfg(feature = "std")]
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub struct PlanStats {
+    /// The number of nodes the search expanded.
+    pub expanded: usize,
+    /// The number of successor nodes the expanded nodes had, counting each time a node is reached.
+    pub generated: usize,
+    /// The greatest number of nodes waiting to be expanded at once, including the initial node.
+    pub peak_open: usize,
+    /// The number of actions in the plan found, or zero when there's none.
+    pub solution_depth: usize,
+    /// The greatest number of actions leading to a node the search expanded.
+    pub max_depth: usize,
+}
+
+#[cfg(feature = "std")]
+impl PlanStats {
+    /// Returns the effective branching factor of the search: the branching factor a uniform tree
+    /// as deep as the plan would need for the nodes generated by the search to fill it.
+    ///
+    /// The closer it is to 1, the better the heuristic guided the search. It's zero when no action
+    /// was planned.
+    pub fn effective_branching_factor(&self) -> f64 {
+        if self.solution_depth == 0 {
+            return 0.0;
+        }
+
+        // The number of nodes of a uniform tree grows with its branching factor, which is thus
+        // found by bisection.
+        let nodes = (self.generated + 1) as f64;
+        let tree_nodes = |factor: f64| {
+            (0..=self.solution_depth).map(|depth| factor.powi(depth as i32)).sum::<f64>()
+        };
+        let (mut low, mut high) = (1.0, nodes.max(1.0));
+        for _ in 0..64 {
+            let middle = (low + high) / 2.0;
+            if tree_nodes(middle) < nodes {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        (low + high) / 2.0
+    }
+}
+
+/// Formulates a plan like `plan` does, and returns it along with statistics about the search.
+#[cfg(feature = "std")]
+pub fn plan_with_stats<'a, G, K>(initial_state: &'a State<K>,
+                                 goal_state: &G,
+                                 allowed_actions: &'a [Action<K>])
+                                 -> (Option<Vec<&'a Action<K>>>, PlanStats)
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    // The search doesn't tell which nodes are waiting to be expanded, so this follows along with
+    // it: nodes are added to the open set whenever they're reached in a cheaper way than before.
+    let mut stats = PlanStats {
+        peak_open: 1,
+        ..PlanStats::default()
+    };
+    let mut depths = HashMap::new();
+    let mut open = HashSet::new();
+    depths.insert(space.initial_node(), 0);
+    open.insert(space.initial_node());
+
+    let result = space.search_with_costs(|node, g_cost, costs| {
+                                             stats.expanded += 1;
+                                             stats.max_depth = stats.max_depth.max(depths[node]);
+                                             open.remove(node);
+
+                                             let successors = node.possible_next_nodes();
+                                             for (child, cost) in &successors {
+                                                 stats.generated += 1;
+                                                 if costs.reach(child, g_cost + cost) {
+                                                     depths.insert(child.clone(), depths[node] + 1);
+                                                     open.insert(child.clone());
+                                                 }
+                                             }
+                                             stats.peak_open = stats.peak_open.max(open.len());
+                                             successors
+                                         },
+                                         |node| node.mismatch_count(&goal),
+                                         |node| node.matches(&goal));
+
+    let planned = result.map(|(path, _)| path_actions(&path, allowed_actions));
+    stats.solution_depth = planned.as_ref().map_or(0, |planned| planned.len());
+    (planned, stats)
+}
+
+/// Formulates a plan like `plan` does, but tells why when no plan can be found.
+pub fn try_plan<'a, G, K>(initial_state: &'a State<K>,
+                          goal_state: &G,
+                          allowed_actions: &'a [Action<K>])
+                          -> Result<Vec<&'a Action<K>>, PlanError<K>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    check_plan_inputs(initial_state, goal_state, allowed_actions)?;
+
+    plan(initial_state, goal_state, allowed_actions).ok_or(PlanError::Unreachable)
+}
+
+/// Formulates a plan like `try_plan` does, after checking that all allowed actions are valid, and
+/// that no two of them have the same name.
+pub fn plan_validated<'a, G, K>(initial_state: &'a State<K>,
+                                goal_state: &G,
+                                allowed_actions: &'a [Action<K>])
+                                -> Result<Vec<&'a Action<K>>, PlanError<K>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    for action in allowed_actions {
+        action.validate().map_err(|error| PlanError::InvalidAction(action.name.clone(), error))?;
+    }
+    for (index, action) in allowed_actions.iter().enumerate() {
+        if allowed_actions[..index].iter().any(|other| other.name == action.name) {
+            return Err(PlanError::DuplicateActionName(action.name.clone()));
+        }
+    }
+
+    try_plan(initial_state, goal_state, allowed_actions)
+}
+
+/// Formulates a plan like `try_plan` does, but gives up once `max_nodes` nodes have been expanded.
+///
+/// When the limit is reached, `PlanError::NodeLimitExceeded` is returned, even if a plan could
+/// still be found among the nodes already seen, as that plan may not be the optimal one.
+pub fn plan_with_limits<'a, G, K>(initial_state: &'a State<K>,
+                                  goal_state: &G,
+                                  allowed_actions: &'a [Action<K>],
+                                  max_nodes: usize)
+                                  -> Result<Vec<&'a Action<K>>, PlanError<K>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    check_plan_inputs(initial_state, goal_state, allowed_actions)?;
+
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    // Once the budget is spent, nodes no longer yield any successor, which drains the search.
+    let mut expanded_nodes: usize = 0;
+    let mut limit_reached = false;
+    let result = space.search_with(|node| if expanded_nodes < max_nodes {
+                                       expanded_nodes += 1;
+                                       node.possible_next_nodes()
+                                   } else {
+                                       limit_reached = true;
+                                       vec![]
+                                   },
+                                   |node| node.mismatch_count(&goal),
+                                   |node| node.matches(&goal));
+
+    match result {
+        _ if limit_reached => Err(PlanError::NodeLimitExceeded),
+        Some((path, _)) => Ok(path_actions(&path, allowed_actions)),
+        None => Err(PlanError::Unreachable),
+    }
+}
+
+/// The number of nodes `plan_with_timeout` and `plan_best_effort_with_timeout` expand between two
+/// looks at the clock.
+#[cfg(feature = "std")]
+const TIMEOUT_CHECK_INTERVAL: usize = 64;
+
+/// Formulates a plan like `try_plan` does, but gives up once it has been searching for `timeout`.
+///
+/// The clock is only looked at every few expanded nodes, as it's costly compared to expanding one,
+/// so the search can run a bit longer than `timeout`. When it's out of time,
+/// `PlanError::Timeout` is returned, even if a plan could still be found among the nodes already
+/// seen, as that plan may not be the optimal one. `plan_best_effort_with_timeout` returns the
+/// closest the search got instead.
+#[cfg(feature = "std")]
+// `usize::is_multiple_of` is only stable since Rust 1.87.
+#[allow(clippy::manual_is_multiple_of)]
+pub fn plan_with_timeout<'a, G, K>(initial_state: &'a State<K>,
+                                   goal_state: &G,
+                                   allowed_actions: &'a [Action<K>],
+                                   timeout: Duration)
+                                   -> Result<Vec<&'a Action<K>>, PlanError<K>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    check_plan_inputs(initial_state, goal_state, allowed_actions)?;
+
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    // As with `plan_with_limits`, nodes no longer yield any successor once the time is up.
+    let start = Instant::now();
+    let mut expanded_nodes: usize = 0;
+    let mut timed_out = false;
+    let result = space.search_with(|node| {
+                                       if expanded_nodes % TIMEOUT_CHECK_INTERVAL == 0 &&
+                                          start.elapsed() >= timeout {
+                                           timed_out = true;
+                                       }
+                                       if timed_out {
+                                           return vec![];
+                                       }
+                                       expanded_nodes += 1;
+                                       node.possible_next_nodes()
+                                   },
+                                   |node| node.mismatch_count(&goal),
+                                   |node| node.matches(&goal));
+
+    match result {
+        _ if timed_out => Err(PlanError::Timeout),
+        Some((path, _)) => Ok(path_actions(&path, allowed_actions)),
+        None => Err(PlanError::Unreachable),
+    }
+}
+
+/// Formulates a plan to get as close as possible to a goal state, and returns it along with the
+/// state of the world once it has been applied.
+///
+/// When the goal state can be reached, this is the plan `plan` would formulate. Otherwise, the plan
+/// leads to the reachable state with the fewest goal atoms not having their goal value yet, and the
+/// cheapest one among those, it being the initial state when no action helps. The search gives up
+/// once `max_nodes` nodes have been expanded, in which case only the states seen until then are
+/// considered.
+#[cfg(feature = "std")]
+pub fn plan_best_effort<'a, G, K>(initial_state: &'a State<K>,
+                                  goal_state: &G,
+                                  allowed_actions: &'a [Action<K>],
+                                  max_nodes: usize)
+                                  -> (Vec<&'a Action<K>>, State<K>)
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    best_effort(initial_state,
+                goal_state,
+                allowed_actions,
+                |expanded_nodes| expanded_nodes < max_nodes)
+}
+
+/// Formulates a plan like `plan_best_effort` does, but gives up once it has been searching for
+/// `timeout` rather than after a number of expanded nodes.
+///
+/// The clock is looked at as often as `plan_with_timeout` does, and when the search is out of time,
+/// the plan leads to the closest state to the goal state among those seen until then.
+#[cfg(feature = "std")]
+pub fn plan_best_effort_with_timeout<'a, G, K>(initial_state: &'a State<K>,
+                                               goal_state: &G,
+                                               allowed_actions: &'a [Action<K>],
+                                               timeout: Duration)
+                                               -> (Vec<&'a Action<K>>, State<K>)
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let start = Instant::now();
+    best_effort(initial_state, goal_state, allowed_actions, |expanded_nodes| {
+        expanded_nodes % TIMEOUT_CHECK_INTERVAL != 0 || start.elapsed() < timeout
+    })
+}
+
+/// Searches for the plans of `plan_best_effort` and `plan_best_effort_with_timeout`, expanding
+/// nodes for as long as `may_expand`, given the number of nodes expanded so far, returns `true`.
+#[cfg(feature = "std")]
+fn best_effort<'a, G, K, F>(initial_state: &'a State<K>,
+                            goal_state: &G,
+                            allowed_actions: &'a [Action<K>],
+                            mut may_expand: F)
+                            -> (Vec<&'a Action<K>>, State<K>)
+    where G: ToGoal<K> + ?Sized,
+          K: Atom,
+          F: FnMut(usize) -> bool
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    // The search only tells the way to the goal, so the cheapest way to every node seen is tracked
+    // here, along with the order in which nodes are seen.
+    let initial = space.initial_node();
+    let mut ways = HashMap::new();
+    ways.insert(initial.clone(), (0, None));
+    let mut seen_nodes = vec![initial];
+
+    let mut expanded_nodes: usize = 0;
+    let result = space.search_with_costs(|node, g_cost, costs| {
+                                             if !may_expand(expanded_nodes) {
+                                                 return vec![];
+                                             }
+                                             expanded_nodes += 1;
+
+                                             let mut successors = vec![];
+                                             for (index, child, cost) in node.transitions() {
+                                                 let child_g_cost = g_cost + cost;
+                                                 if costs.reach(&child, child_g_cost) {
+                                                     let way = (child_g_cost,
+                                                                Some((node.clone(), index)));
+                                                     if ways.insert(child.clone(), way).is_none() {
+                                                         seen_nodes.push(child.clone());
+                                                     }
+                                                 }
+                                                 successors.push((child, cost));
+                                             }
+                                             successors
+                                         },
+                                         |node| node.mismatch_count(&goal),
+                                         |node| node.matches(&goal));
+
+    if let Some((path, _)) = result {
+        let final_state = path.last().unwrap().current_state();
+        return (path_actions(&path, allowed_actions), final_state);
+    }
+
+    let closest = seen_nodes.iter()
+        .min_by_key(|node| (node.mismatch_count(&goal), ways[*node].0))
+        .unwrap();
+
+    let mut actions = vec![];
+    let mut node = closest;
+    while let Some((ref parent, index)) = ways[node].1 {
+        actions.push(&allowed_actions[index]);
+        node = parent;
+    }
+    actions.reverse();
+
+    (actions, closest.current_state())
+}
+
+/// Returns `true` if a goal state can be reached from an initial state using a set of allowed
+/// actions, like `plan(..).is_some()` would, without looking for the cheapest way there.
+///
+/// The reachable states are walked through in number of actions from the initial state, until one
+/// reaching the goal state is found. Like with `plan`, relative effects can make the number of
+/// reachable states infinite, in which case an unreachable goal makes this run forever.
+#[cfg(feature = "std")]
+pub fn is_reachable<G, K>(initial_state: &State<K>,
+                          goal_state: &G,
+                          allowed_actions: &[Action<K>])
+                          -> bool
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    let mut space = SearchSpace::new(initial_state, allowed_actions, &());
+    let goal = space.goal(goal_state);
+
+    let initial = space.initial_node();
+    let mut seen_nodes = HashSet::new();
+    let mut queue = VecDeque::new();
+    seen_nodes.insert(initial.clone());
+    queue.push_back(initial);
+
+    while let Some(node) = queue.pop_front() {
+        if node.matches(&goal) {
+            return true;
+        }
+        for (child, _) in node.possible_next_nodes() {
+            if seen_nodes.insert(child.clone()) {
+                queue.push_back(child);
+            }
+        }
+    }
+
+    false
+}
+
+/// Returns each state reachable from an initial state using a set of allowed actions for at most
+/// `max_cost`, along with the cost of the cheapest way to reach it.
+///
+/// The states come in order of increasing cost, starting with the initial state, for free.
+pub fn reachable_states<K: Atom>(initial_state: &State<K>,
+                                 allowed_actions: &[Action<K>],
+                                 max_cost: usize)
+                                 -> Vec<(State<K>, usize)> {
+    let space = SearchSpace::new(initial_state, allowed_actions, &());
+
+    // The same state can make several nodes, when different actions are left to apply once there,
+    // so states are only listed the first time, which is the cheapest.
+    let mut states = vec![];
+    let mut listed_states = BTreeSet::new();
+    for (node, g_cost) in space.by_cost(max_cost) {
+        let state = node.current_state();
+        if listed_states.insert(state.clone()) {
+            states.push((state, g_cost));
+        }
+    }
+
+    states
+}
+
+/// Checks for the planning inputs that can't possibly lead to a plan, before searching for one.
+fn check_plan_inputs<G, K>(initial_state: &State<K>,
+                           goal_state: &G,
+                           allowed_actions: &[Action<K>])
+                           -> Result<(), PlanError<K>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom
+{
+    // Without any actions, the goal state has to be reached already, in which case the empty plan
+    // gets there.
+    let goal = goal_state.to_goal();
+    let mut unmet_atoms = goal.iter()
+        .filter(|&(name, goal_atom)| !goal_atom.is_met_by(initial_state.get(name)))
+        .peekable();
+    if allowed_actions.is_empty() && unmet_atoms.peek().is_some() {
+        return Err(PlanError::EmptyActionSet);
+    }
+
+    // Every goal atom the initial state doesn't fulfill has to be changed by some action, or
+    // there's no point in searching. Atoms are never removed, so absent atoms can't be fixed.
+    for (name, goal_atom) in unmet_atoms {
+        let is_changed =
+            allowed_actions.iter().any(|action| action.post_conditions.contains_key(name));
+        match *goal_atom {
+            GoalAtom::MustBeAbsent => return Err(PlanError::ImmutableGoalAtom(name.clone())),
+            _ if is_changed => {}
+            _ if initial_state.contains_key(name) => {
+                return Err(PlanError::ImmutableGoalAtom(name.clone()))
+            }
+            _ => return Err(PlanError::GoalAtomNeverProduced(name.clone())),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the initial state, goal state and actions of a door to open, either with a key for
+    /// 2 in all, or by smashing it for 5.
+    pub fn key_and_door() -> (State, State, Vec<Action>) {
+        let actions = vec![Action::new("get_key", 1).produces("has_key", true),
+                           Action::new("open_door", 1)
+                               .requires("has_key", true)
+                               .produces("door_open", true),
+                           Action::new("smash_door", 5).produces("door_open", true)];
+        let initial_state = state([("has_key", false), ("door_open", false)]);
+        let goal_state = state([("door_open", true)]);
+        (initial_state, goal_state, actions)
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        let mut action = Action::new("action".to_string(), 1);
+        action.pre_conditions.insert("has_something".to_string(), true.into());
+        action.post_conditions.insert("is_winning".to_string(), true.into());
+
+        let actions = [action];
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_something".to_string(), false.into());
+        initial_state.insert("is_winning".to_string(), false.into());
+
+        // No viable plan.
+        {
+            let mut goal_state = State::new();
+            goal_state.insert("is_winning".to_string(), true.into());
+
+            let plan = plan(&initial_state, &goal_state, &actions);
+            assert!(plan.is_none());
+        }
+
+        // The goal state is already reached in the initial state.
+        {
+            let mut goal_state = State::new();
+            goal_state.insert("is_winning".to_string(), false.into());
+
+            let plan = plan(&initial_state, &goal_state, &actions);
+            assert!(plan.unwrap().is_empty());
+        }
+
+        // The goal state uses a state missing from the initial state.
+        {
+            let mut goal_state = State::new();
+            goal_state.insert("is_losing".to_string(), false.into());
+
+            let plan = plan(&initial_state, &goal_state, &actions);
+            assert!(plan.is_none());
+        }
+    }
+
+    #[test]
+    fn test_try_plan_errors() {
+        let mut action = Action::new("action".to_string(), 1);
+        action.pre_conditions.insert("has_something".to_string(), true.into());
+        action.post_conditions.insert("is_winning".to_string(), true.into());
+
+        let actions = [action];
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_something".to_string(), false.into());
+        initial_state.insert("is_winning".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("is_winning".to_string(), true.into());
+        assert_eq!(try_plan(&initial_state, &goal_state, &actions),
+                   Err(PlanError::Unreachable));
+        assert_eq!(try_plan(&initial_state, &goal_state, &[]),
+                   Err(PlanError::EmptyActionSet));
+
+        // No actions are needed when the goal state is reached already.
+        let mut reached_goal_state = State::new();
+        reached_goal_state.insert("is_winning".to_string(), false.into());
+        assert_eq!(try_plan(&initial_state, &reached_goal_state, &[]), Ok(vec![]));
+        assert_eq!(try_plan(&initial_state, &State::new(), &[]), Ok(vec![]));
+
+        goal_state.insert("is_losing".to_string(), false.into());
+        assert_eq!(try_plan(&initial_state, &goal_state, &actions),
+                   Err(PlanError::GoalAtomNeverProduced("is_losing".to_string())));
+
+        // Nothing can give something, so the goal can be told unreachable without searching.
+        let mut goal_state = State::new();
+        goal_state.insert("has_something".to_string(), true.into());
+        assert_eq!(try_plan(&initial_state, &goal_state, &actions),
+                   Err(PlanError::ImmutableGoalAtom("has_something".to_string())));
+        assert_eq!(plan(&initial_state, &goal_state, &actions), None);
+        let mut space = SearchSpace::new(&initial_state, &actions, &());
+        let goal = space.goal(&goal_state);
+        assert_eq!(space.immutable_goal_atom(&goal), Some(&"has_something".to_string()));
+
+        initial_state.insert("has_something".to_string(), true.into());
+        let mut goal_state = State::new();
+        goal_state.insert("is_winning".to_string(), true.into());
+        assert_eq!(try_plan(&initial_state, &goal_state, &actions).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_plan_with_limits() {
+        // An endless chain of counting actions, of which only the last one wins.
+        let mut actions = vec![];
+        for i in 0..10 {
+            let mut action = Action::new(format!("step_{}", i), 1);
+            action.pre_conditions.insert(format!("step_{}", i), true.into());
+            action.post_conditions.insert(format!("step_{}", i + 1), true.into());
+            actions.push(action);
+        }
+
+        let mut initial_state = State::new();
+        initial_state.insert("step_0".to_string(), true.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("step_10".to_string(), true.into());
+
+        assert_eq!(plan_with_limits(&initial_state, &goal_state, &actions, 5),
+                   Err(PlanError::NodeLimitExceeded));
+        assert_eq!(plan_with_limits(&initial_state, &goal_state, &actions, 100).unwrap().len(),
+                   10);
+
+        // No expansion is needed at all when the goal is reached from the start.
+        assert!(plan_with_limits(&initial_state, &initial_state, &actions, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_plan_with_timeout() {
+        let mut actions = vec![];
+        for i in 0..10 {
+            actions.push(Action::new(format!("step_{}", i), 1)
+                .requires(format!("step_{}", i), true)
+                .produces(format!("step_{}", i + 1), true));
+        }
+
+        let mut initial_state = State::new();
+        initial_state.insert("step_0".to_string(), true.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("step_10".to_string(), true.into());
+
+        // The clock is looked at before expanding the first node.
+        assert_eq!(plan_with_timeout(&initial_state, &goal_state, &actions, Duration::new(0, 0)),
+                   Err(PlanError::Timeout));
+        let timeout = Duration::new(60, 0);
+        let planned = plan_with_timeout(&initial_state, &goal_state, &actions, timeout);
+        assert_eq!(planned.unwrap().len(), 10);
+
+        assert!(plan_with_timeout(&initial_state, &initial_state, &actions, Duration::new(0, 0))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_non_boolean_values() {
+        const SWORD: u32 = 1;
+        const AXE: u32 = 2;
+
+        let mut grab_axe = Action::new("grab_axe".to_string(), 1);
+        grab_axe.post_conditions.insert("weapon".to_string(), Value::Enum(AXE).into());
+
+        let mut chop = Action::new("chop".to_string(), 1);
+        chop.pre_conditions.insert("weapon".to_string(), Value::Enum(AXE).into());
+        chop.pre_conditions.insert("wood".to_string(), 0.into());
+        chop.post_conditions.insert("wood".to_string(), 3.into());
+
+        let actions = [chop, grab_axe];
+
+        let mut initial_state = State::new();
+        initial_state.insert("weapon".to_string(), Value::Enum(SWORD));
+        initial_state.insert("wood".to_string(), 0.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("wood".to_string(), 3.into());
+
+        let plan = plan(&initial_state, &goal_state, &actions).unwrap();
+        let names: Vec<&str> = plan.iter().map(|action| action.name.as_str()).collect();
+        assert_eq!(names, vec!["grab_axe", "chop"]);
+    }
+
+    #[test]
+    fn test_numeric_conditions() {
+        let mut chop = Action::new("chop".to_string(), 1);
+        chop.post_conditions.insert("wood".to_string(), Effect::Add(1));
+
+        let mut build = Action::new("build".to_string(), 1);
+        build.pre_conditions.insert("wood".to_string(), Condition::GreaterOrEqual(3));
+        build.post_conditions.insert("wood".to_string(), Effect::Add(-3));
+        build.post_conditions.insert("has_house".to_string(), true.into());
+
+        let actions = [build, chop];
+
+        let mut initial_state = State::new();
+        initial_state.insert("wood".to_string(), 1.into());
+        initial_state.insert("has_house".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("has_house".to_string(), true.into());
+        goal_state.insert("wood".to_string(), 0.into());
+
+        let plan = plan(&initial_state, &goal_state, &actions).unwrap();
+        let names: Vec<&str> = plan.iter().map(|action| action.name.as_str()).collect();
+        assert_eq!(names, vec!["chop", "chop", "build"]);
+    }
+
+    #[test]
+    fn test_condition_comparisons() {
+        let three = Value::Int(3);
+        assert!(Condition::Equal(three).is_met_by(Some(&three)));
+        assert!(Condition::GreaterOrEqual(3).is_met_by(Some(&three)));
+        assert!(!Condition::Greater(3).is_met_by(Some(&three)));
+        assert!(Condition::LessOrEqual(3).is_met_by(Some(&three)));
+        assert!(!Condition::Less(3).is_met_by(Some(&three)));
+        assert!(!Condition::GreaterOrEqual(0).is_met_by(Some(&Value::Bool(true))));
+        assert!(!Condition::GreaterOrEqual(0).is_met_by(None));
+
+        assert_eq!(Effect::Add(-3).apply_to(Some(&three)), Some(Value::Int(0)));
+        assert_eq!(Effect::Add(2).apply_to(None), Some(Value::Int(2)));
+        assert_eq!(Effect::Add(2).apply_to(Some(&Value::Enum(1))), Some(Value::Enum(1)));
+
+        assert_eq!(Effect::Toggle.apply_to(Some(&Value::Bool(true))), Some(Value::Bool(false)));
+        assert_eq!(Effect::Toggle.apply_to(None), Some(Value::Bool(true)));
+        assert_eq!(Effect::Toggle.apply_to(Some(&three)), Some(three));
+    }
+
+    #[test]
+    fn test_toggles() {
+        // Both lights have to be on, but a switch flips both of them at once.
+        let actions = [Action::new("flip_both", 1)
+                           .produces("left_light", Effect::Toggle)
+                           .produces("right_light", Effect::Toggle),
+                       Action::new("flip_left", 1).produces("left_light", Effect::Toggle)];
+        let initial_state = state([("left_light", true), ("right_light", false)]);
+        let goal_state = state([("left_light", true), ("right_light", true)]);
+
+        let planned = plan(&initial_state, &goal_state, &actions).unwrap();
+        let names: Vec<&str> = planned.iter().map(|action| action.name.as_str()).collect();
+        assert_eq!(names, vec!["flip_both", "flip_left"]);
+        let mut state = initial_state.clone();
+        assert_eq!(execute(&planned, &mut state), Ok(()));
+        assert_eq!(state, goal_state);
+    }
+
+    #[test]
+    fn test_plan_with_states() {
+        let mut chop = Action::new("chop".to_string(), 1);
+        chop.post_conditions.insert("wood".to_string(), Effect::Add(1));
+
+        let actions = [chop];
+
+        let mut initial_state = State::new();
+        initial_state.insert("wood".to_string(), 0.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("wood".to_string(), 2.into());
+
+        let steps = plan_with_states(&initial_state, &goal_state, &actions).unwrap();
+        let woods: Vec<Value> = steps.iter().map(|step| step.1["wood"]).collect();
+        assert_eq!(woods, vec![Value::Int(1), Value::Int(2)]);
+        assert!(steps.iter().all(|&(action, _)| action.name == "chop"));
+
+        assert!(plan_with_states(&initial_state, &initial_state, &actions).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_action_builder() {
+        let built = Action::new("build", 2)
+            .requires("wood", Condition::GreaterOrEqual(3))
+            .requires("has_axe", true)
+            .produces("wood", Effect::Add(-3))
+            .produces("has_house", true);
+
+        let mut inserted = Action::new("build".to_string(), 2);
+        inserted.pre_conditions.insert("wood".to_string(), Condition::GreaterOrEqual(3));
+        inserted.pre_conditions.insert("has_axe".to_string(), true.into());
+        inserted.post_conditions.insert("wood".to_string(), Effect::Add(-3));
+        inserted.post_conditions.insert("has_house".to_string(), true.into());
+
+        assert_eq!(built, inserted);
+    }
+
+    #[test]
+    fn test_plan_any() {
+        let go_home = Action::new("go_home", 5).produces("at_home", true);
+        let go_bunker = Action::new("go_bunker", 2).produces("in_bunker", true);
+        let actions = [go_home, go_bunker];
+
+        let mut initial_state = State::new();
+        initial_state.insert("at_home".to_string(), false.into());
+        initial_state.insert("in_bunker".to_string(), false.into());
+
+        let mut at_home = State::new();
+        at_home.insert("at_home".to_string(), true.into());
+        let mut in_bunker = State::new();
+        in_bunker.insert("in_bunker".to_string(), true.into());
+
+        let goal_states = [at_home.clone(), in_bunker.clone()];
+        let (plan, index) = plan_any(&initial_state, &goal_states, &actions).unwrap();
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].name, "go_bunker");
+        assert_eq!(index, 1);
+
+        // Already being safe at home is the cheapest way to be safe.
+        initial_state.insert("at_home".to_string(), true.into());
+        let (plan, index) = plan_any(&initial_state, &[in_bunker, at_home], &actions).unwrap();
+        assert!(plan.is_empty());
+        assert_eq!(index, 1);
+
+        assert!(plan_any::<State, _>(&initial_state, &[], &actions).is_none());
+    }
+
+    #[test]
+    fn test_plan_batch() {
+        let (mut initial_state, _, mut actions) = key_and_door();
+        actions.push(Action::new("enter", 1).requires("door_open", true).produces("inside", true));
+        initial_state.insert("inside".to_string(), false.into());
+
+        let goal_states: Vec<State> = [("inside", true), ("has_key", true), ("window_open", true),
+                                       ("door_open", false)]
+            .iter()
+            .map(|&(name, value)| {
+                let mut goal_state = State::new();
+                goal_state.insert(name.to_string(), value.into());
+                goal_state
+            })
+            .collect();
+
+        let plans = plan_batch(&initial_state, &goal_states, &actions);
+        assert_eq!(plans.len(), 4);
+        for (planned, goal_state) in plans.iter().zip(&goal_states) {
+            assert_eq!(*planned, plan(&initial_state, goal_state, &actions));
+        }
+        assert_eq!(plans[0].as_ref().unwrap().len(), 3);
+        assert_eq!(plans[2], None);
+        assert_eq!(plans[3], Some(vec![]));
+
+        assert!(plan_batch::<State, _>(&initial_state, &[], &actions).is_empty());
+    }
+
+    #[test]
+    fn test_plan_weighted() {
+        // A long cheap detour, and a short expensive shortcut.
+        let mut actions = vec![Action::new("shortcut", 3).produces("a", true).produces("b", true)];
+        actions.push(Action::new("get_a", 1).produces("a", true));
+        actions.push(Action::new("get_b", 1).requires("a", true).produces("b", true));
+
+        let mut initial_state = State::new();
+        initial_state.insert("a".to_string(), false.into());
+        initial_state.insert("b".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("a".to_string(), true.into());
+        goal_state.insert("b".to_string(), true.into());
+
+        let names = |plan: Vec<&Action>| -> Vec<String> {
+            plan.iter().map(|action| action.name.clone()).collect()
+        };
+
+        let optimal = plan_weighted(&initial_state, &goal_state, &actions, 1.0).unwrap();
+        assert_eq!(names(optimal), vec!["get_a", "get_b"]);
+
+        // Greedier searches settle for a more expensive plan, within bounds.
+        let greedy = plan_weighted(&initial_state, &goal_state, &actions, 5.0).unwrap();
+        assert_eq!(names(greedy), vec!["shortcut"]);
+    }
+
+    #[test]
+    fn test_plan_with_heuristic() {
+        let mine = Action::new("mine", 10).produces("has_gold", true);
+        let buy = Action::new("buy", 2).requires("has_gold", true).produces("has_sword", true);
+        let actions = [mine, buy];
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_gold".to_string(), false.into());
+        initial_state.insert("has_sword".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("has_sword".to_string(), true.into());
+
+        let mut estimates = 0;
+        let plan = plan_with_heuristic(&initial_state, &goal_state, &actions, |state, _| {
+            estimates += 1;
+            match state.get("has_gold") {
+                Some(&Value::Bool(true)) => 2,
+                _ => 12,
+            }
+        });
+        let names: Vec<&str> = plan.unwrap().iter().map(|action| action.name.as_str()).collect();
+        assert_eq!(names, vec!["mine", "buy"]);
+        assert!(estimates > 0);
+    }
+
+    #[test]
+    fn test_plan_until() {
+        let flags = ["a", "b", "c", "d", "e"];
+        let actions: Vec<Action> = flags.iter()
+            .enumerate()
+            .map(|(index, &flag)| {
+                Action::new(format!("set_{}", flag), index + 1).produces(flag, true)
+            })
+            .collect();
+        let initial_state = state(flags.iter().map(|&flag| (flag, false)));
+
+        // At least 3 of the 5 flags are set, the cheapest ones being the first ones.
+        let set_count = |state: &State| {
+            state.values().filter(|&&value| value == Value::Bool(true)).count()
+        };
+        let heuristic = |state: &State| 3 - set_count(state).min(3);
+        let is_goal = |state: &State| set_count(state) >= 3;
+        let planned = plan_until(&initial_state, is_goal, &actions, heuristic).unwrap();
+        let mut names: Vec<&str> = planned.iter().map(|action| action.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["set_a", "set_b", "set_c"]);
+
+        // A heuristic estimating no cost left gives a plan as cheap.
+        let planned = plan_until(&initial_state, is_goal, &actions, |_| 0);
+        assert_eq!(planned.unwrap().iter().map(|action| action.cost).sum::<usize>(), 6);
+        assert_eq!(plan_until(&initial_state, |state| set_count(state) == 6, &actions, |_| 0),
+                   None);
+    }
+
+    #[test]
+    fn test_plan_sequence() {
+        let actions = [Action::new("go_to_forest", 1)
+                           .requires("in_village", true)
+                           .produces("in_village", false),
+                       Action::new("go_to_village", 1)
+                           .requires("in_village", false)
+                           .produces("in_village", true),
+                       Action::new("chop_wood", 1)
+                           .requires("in_village", false)
+                           .produces("has_wood", true),
+                       Action::new("sell_wood", 1)
+                           .requires("in_village", true)
+                           .requires("has_wood", true)
+                           .produces("has_wood", false)
+                           .produces("has_gold", true)];
+
+        let initial_state = state([("in_village", true), ("has_wood", false)]);
+        let subgoals = [state([("has_wood", true)]), state([("has_gold", true)])];
+
+        // Each leg starts from where the previous one left off.
+        let planned = plan_sequence(&initial_state, &subgoals, &actions).unwrap();
+        let names: Vec<&str> = planned.iter().map(|action| action.name.as_str()).collect();
+        assert_eq!(names, vec!["go_to_forest", "chop_wood", "go_to_village", "sell_wood"]);
+
+        // Once earned, the gold can't be spent, so the second subgoal can't be achieved.
+        let subgoals = [state([("has_gold", true)]), state([("has_gold", false)])];
+        assert_eq!(plan_sequence(&initial_state, &subgoals, &actions), Err(1));
+        assert_eq!(plan_sequence::<State, _>(&initial_state, &[], &actions), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_plan_dijkstra() {
+        // Without a heuristic, the plans are as cheap as those of `plan`.
+        for seed in 0..20 {
+            let (initial_state, goal_state, actions) = generate_domain(12, 40, seed);
+            let expected = plan_with_cost(&initial_state, &goal_state, &actions);
+            let planned = plan_dijkstra(&initial_state, &goal_state, &actions);
+            assert_eq!(planned.map(|plan| plan.cost), expected.map(|plan| plan.cost));
+        }
+
+        let (initial_state, goal_state, actions) = key_and_door();
+        let planned = plan_dijkstra(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(planned.names(), vec!["get_key", "open_door"]);
+        assert_eq!(planned.cost, 2);
+    }
+
+    #[test]
+    fn test_state() {
+        let mut expected = State::new();
+        expected.insert("near_dog".to_string(), false.into());
+        expected.insert("dog_person".to_string(), true.into());
+        assert_eq!(state([("near_dog", false), ("dog_person", true)]), expected);
+
+        // Names can be owned, values can be mixed, and the state grows like any other.
+        let mut built = state(vec![("wood".to_string(), Value::Int(3)),
+                                   ("has_axe".to_string(), Value::Bool(true))]);
+        built.insert("near_tree".to_string(), false.into());
+        assert_eq!(built.len(), 3);
+        assert_eq!(built["wood"], Value::Int(3));
+    }
+
+    #[test]
+    fn test_state_comparison() {
+        let old = state(vec![("door_open", Value::Bool(false)),
+                             ("has_key", Value::Bool(true)),
+                             ("wood", Value::Int(3))]);
+        let new = state(vec![("door_open", Value::Bool(true)),
+                             ("has_key", Value::Bool(true)),
+                             ("inside", Value::Bool(true))]);
+        assert_eq!(state_delta(&old, &new),
+                   vec![("door_open".to_string(), Some(false.into()), Some(true.into())),
+                        ("inside".to_string(), None, Some(true.into())),
+                        ("wood".to_string(), Some(3.into()), None)]);
+        assert_eq!(state_delta(&old, &old), vec![]);
+
+        // The hash only depends on the atoms and their values, and never changes.
+        assert_eq!(hash_state(&old), hash_state(&old.clone()));
+        assert_ne!(hash_state(&old), hash_state(&new));
+        assert_ne!(hash_state(&state([("wood", 1)])), hash_state(&state([("wood", true)])));
+        assert_eq!(hash_state::<String>(&State::new()), 0xcbf2_9ce4_8422_2325);
+        assert_ne!(hash_state(&state([("weapon", Value::Enum(1))])),
+                   hash_state(&state([("weapon", 1)])));
+    }
+
+    #[test]
+    fn test_action_validation() {
+        assert_eq!(Action::new("idle", 1).requires("awake", true).validate(),
+                   Err(ActionError::NoPostConditions));
+        assert_eq!(Action::new("open", 1)
+                       .requires("door_open", true)
+                       .produces("door_open", true)
+                       .validate(),
+                   Err(ActionError::RedundantEffect("door_open".to_string())));
+        assert_eq!(Action::new("wait", 1).produces("wood", Effect::Add(0)).validate(),
+                   Err(ActionError::RedundantEffect("wood".to_string())));
+        assert_eq!(Action::new("close", 1)
+                       .requires("door_open", true)
+                       .produces("door_open", false)
+                       .validate(),
+                   Ok(()));
+        assert_eq!(Action::new("blink", 0).produces("eyes_closed", true).validate(),
+                   Err(ActionError::ZeroCost));
+        assert_eq!(Action::new("blink", 0).costs(|_| 1).produces("eyes_closed", true).validate(),
+                   Ok(()));
+
+        let actions = [Action::new("open", 1).produces("door_open", true),
+                       Action::new("idle", 1)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("door_open".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("door_open".to_string(), true.into());
+
+        assert!(try_plan(&initial_state, &goal_state, &actions).is_ok());
+        assert_eq!(plan_validated(&initial_state, &goal_state, &actions),
+                   Err(PlanError::InvalidAction("idle".to_string(),
+                                                ActionError::NoPostConditions)));
+        assert!(plan_validated(&initial_state, &goal_state, &actions[..1]).is_ok());
+
+        let actions = [Action::new("open", 1).produces("door_open", true),
+                       Action::new("open", 2).produces("window_open", true)];
+        assert_eq!(plan_validated(&initial_state, &goal_state, &actions),
+                   Err(PlanError::DuplicateActionName("open".to_string())));
+    }
+
+    #[test]
+    fn test_merge_actions() {
+        let doors: Vec<Action> = vec![Action::new("open_door", 1).produces("door_open", true),
+                                      Action::new("close_door", 1).produces("door_open", false)];
+        let keys = vec![Action::new("get_key", 1).produces("has_key", true),
+                        Action::new("open_door", 1).produces("door_open", true)];
+        let windows = vec![Action::new("open_window", 1).produces("window_open", true)];
+
+        // Identical actions shared by several libraries are merged once.
+        let merged = merge_actions(&[&doors, &keys, &windows]).unwrap();
+        let names: Vec<&str> = merged.iter().map(|action| action.name.as_str()).collect();
+        assert_eq!(names, vec!["open_door", "close_door", "get_key", "open_window"]);
+
+        // Different actions with the same name can't both be kept.
+        let smashing = vec![Action::new("open_door", 5).produces("door_open", true)];
+        assert_eq!(merge_actions(&[&doors, &smashing]),
+                   Err(MergeError::Duplicate("open_door".to_string())));
+
+        // Neither can actions whose functions can't be compared.
+        let checked = vec![Action::new("open_door", 1).produces("door_open", true).costs(|_| 1)];
+        assert_eq!(merge_actions(&[&checked, &checked]),
+                   Err(MergeError::Duplicate("open_door".to_string())));
+        assert_eq!(merge_actions::<String, ()>(&[]), Ok(vec![]));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_plan_all_optimal() {
+        let actions = [Action::new("walk", 2).produces("at_door", true),
+                       Action::new("run", 1).produces("tired", true),
+                       Action::new("sprint", 1).requires("tired", true).produces("at_door", true),
+                       Action::new("crawl", 2).produces("at_door", true),
+                       Action::new("teleport", 5).produces("at_door", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("at_door".to_string(), false.into());
+        initial_state.insert("tired".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("at_door".to_string(), true.into());
+
+        let plans: Vec<Vec<&str>> = plan_all_optimal(&initial_state, &goal_state, &actions)
+            .iter()
+            .map(|plan| plan.iter().map(|action| action.name.as_str()).collect())
+            .collect();
+        assert_eq!(plans, vec![vec!["crawl"], vec!["run", "sprint"], vec!["walk"]]);
+
+        goal_state.insert("flying".to_string(), true.into());
+        assert!(plan_all_optimal(&initial_state, &goal_state, &actions).is_empty());
+    }
+
+    #[test]
+    fn test_merge_goals() {
+        let mut combat_goal = State::new();
+        combat_goal.insert("enemy_dead".to_string(), true.into());
+        combat_goal.insert("health".to_string(), 3.into());
+
+        let mut survival_goal = State::new();
+        survival_goal.insert("health".to_string(), 3.into());
+        survival_goal.insert("hungry".to_string(), false.into());
+
+        let merged = merge_goals(&[combat_goal.clone(), survival_goal.clone()]).unwrap();
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged["enemy_dead"], true.into());
+        assert_eq!(merged["health"], 3.into());
+        assert_eq!(merged["hungry"], false.into());
+
+        // Both fragments can't have it their way.
+        survival_goal.insert("health".to_string(), 5.into());
+        let conflict = merge_goals(&[combat_goal, survival_goal]).unwrap_err();
+        assert_eq!(conflict,
+                   GoalConflict {
+                       atom: "health".to_string(),
+                       first: 3.into(),
+                       second: 5.into(),
+                   });
+        assert_eq!(conflict.to_string(), "`health` is required to be both Int(3) and Int(5)");
+    }
+
+    #[test]
+    fn test_goals_achieved() {
+        let actions = [Action::new("pet_dog", 1).produces("dog_happy", true),
+                       Action::new("dog_wiggles_tail", 1)
+                           .requires("dog_happy", true)
+                           .produces("tails_wiggling", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("dog_person".to_string(), true.into());
+        initial_state.insert("dog_happy".to_string(), false.into());
+        initial_state.insert("tails_wiggling".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("dog_person".to_string(), true.into());
+        goal_state.insert("dog_happy".to_string(), true.into());
+        goal_state.insert("tails_wiggling".to_string(), true.into());
+        goal_state.insert("cat_happy".to_string(), true.into());
+
+        let mut final_state = initial_state.clone();
+        execute(&[&actions[0], &actions[1]], &mut final_state).unwrap();
+
+        // Being a dog person was already the case, and nothing made the cat happy.
+        assert_eq!(goals_achieved(&initial_state, &goal_state, &final_state),
+                   vec!["dog_happy".to_string(), "tails_wiggling".to_string()]);
+        assert!(goals_achieved(&initial_state, &goal_state, &initial_state).is_empty());
+    }
+
+    #[test]
+    fn test_goal_atoms() {
+        let actions = [Action::new("light_candle", 1).produces("candle_lit", true),
+                       Action::new("light_torch", 3).produces("torch_lit", true),
+                       Action::new("open_shutters", 2).produces("daylight", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("candle_lit".to_string(), false.into());
+
+        // Light, but without introducing a torch in the room.
+        let mut goal = Goal::new();
+        goal.insert("daylight".to_string(), true.into());
+        goal.insert("torch_lit".to_string(), GoalAtom::MustBeAbsent);
+
+        let planned_actions = plan(&initial_state, &goal, &actions).unwrap();
+        let names: Vec<&str> = planned_actions.iter().map(|action| action.name.as_str()).collect();
+        assert_eq!(names, vec!["open_shutters"]);
+
+        // An atom of the initial state can never be absent.
+        goal.insert("candle_lit".to_string(), GoalAtom::MustBeAbsent);
+        assert!(plan(&initial_state, &goal, &actions).is_none());
+
+        // Atoms that must be absent don't need to be produced by anything.
+        let mut goal = Goal::new();
+        goal.insert("dragon".to_string(), GoalAtom::MustBeAbsent);
+        assert_eq!(try_plan(&initial_state, &goal, &actions).unwrap().len(), 0);
+
+        // Present atoms are fulfilled by any value, whether it's from the initial state or not.
+        let mut goal = Goal::new();
+        goal.insert("candle_lit".to_string(), GoalAtom::Present);
+        assert_eq!(plan(&initial_state, &goal, &actions).unwrap().len(), 0);
+        goal.insert("torch_lit".to_string(), GoalAtom::Present);
+        let planned_actions = plan(&initial_state, &goal, &actions).unwrap();
+        assert_eq!(planned_actions, vec![&actions[1]]);
+        #[cfg(feature = "std")]
+        assert_eq!(plan_backward(&initial_state, &goal, &actions), Some(planned_actions));
+    }
+
+    #[test]
+    fn test_alternative_pre_conditions() {
+        use execute::unmet_pre_condition;
+
+        let lockpicking = state([("has_lockpick", true), ("knows_lockpicking", true)]);
+        let actions = [Action::new("open_door", 1)
+                           .requires("at_door", true)
+                           .or_requires(state([("has_key", true)]))
+                           .or_requires(lockpicking)
+                           .produces("door_open", true),
+                       Action::new("walk_to_door", 1).produces("at_door", true),
+                       Action::new("buy_lockpick", 1).produces("has_lockpick", true),
+                       Action::new("read_book", 5).produces("knows_lockpicking", true)];
+        let initial_state = state([("at_door", false), ("has_key", false)]);
+        let goal_state = state([("door_open", true)]);
+
+        // Without a key, the other alternative takes both a lockpick and the knowledge to use it.
+        let planned = plan_with_cost(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(planned.cost, 8);
+        assert_eq!(planned.names().last().unwrap(), "open_door");
+        let mut state = initial_state.clone();
+        assert_eq!(execute(&planned.actions, &mut state), Ok(()));
+        assert_eq!(state["door_open"], Value::Bool(true));
+
+        // Having the key is enough, but the other pre-conditions still have to be fulfilled.
+        let mut has_key = initial_state.clone();
+        has_key.insert("has_key".to_string(), true.into());
+        let planned = plan_with_cost(&has_key, &goal_state, &actions).unwrap();
+        assert_eq!(planned.names(), vec!["walk_to_door", "open_door"]);
+        has_key.insert("at_door".to_string(), true.into());
+        assert_eq!(unmet_pre_condition(&actions[0], &has_key), None);
+
+        // The first alternative the state lacks is reported when none is met.
+        let mut state = initial_state.clone();
+        state.insert("at_door".to_string(), true.into());
+        assert_eq!(unmet_pre_condition(&actions[0], &state), Some(&"has_key".to_string()));
+
+        // Only the alternatives which can't be met keep the action from being applied.
+        assert_eq!(plan(&initial_state, &goal_state, &actions[..3]), None);
+    }
+
+    #[test]
+    fn test_relational_pre_conditions() {
+        let same = RelationalPre {
+            left: "wants_item".to_string(),
+            right: "has_item".to_string(),
+            equal: true,
+        };
+        let different = RelationalPre { equal: false, ..same.clone() };
+        let (one, two) = (Value::Enum(1), Value::Enum(2));
+
+        assert!(same.is_met_by(Some(&one), Some(&one)));
+        assert!(!same.is_met_by(Some(&one), Some(&two)));
+        assert!(!different.is_met_by(Some(&one), Some(&one)));
+        assert!(different.is_met_by(Some(&one), Some(&two)));
+
+        // Absent atoms fulfill neither kind of relational pre-condition.
+        for pre in &[&same, &different] {
+            assert!(!pre.is_met_by(None, Some(&one)));
+            assert!(!pre.is_met_by(Some(&one), None));
+            assert!(!pre.is_met_by(None, None));
+        }
+
+        // Using an item only works once it's the one wanted, which takes swapping it first.
+        let actions = [Action::new("use_item", 1)
+                           .requires_equal("wants_item", "has_item")
+                           .produces("satisfied", true),
+                       Action::new("swap_item", 1)
+                           .requires_different("wants_item", "has_item")
+                           .produces("has_item", Value::Enum(2))];
+
+        let mut initial_state = State::new();
+        initial_state.insert("wants_item".to_string(), Value::Enum(2));
+        initial_state.insert("has_item".to_string(), Value::Enum(1));
+
+        let mut goal_state = State::new();
+        goal_state.insert("satisfied".to_string(), true.into());
+
+        let planned = plan_with_cost(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(planned.names(), vec!["swap_item", "use_item"]);
+
+        let mut state = initial_state.clone();
+        assert_eq!(execute(&planned.actions, &mut state), Ok(()));
+        assert_eq!(execute(&[&actions[1]], &mut state),
+                   Err(ExecError::PreconditionFailed {
+                       action: "swap_item".to_string(),
+                       atom: "wants_item".to_string(),
+                   }));
+
+        // Without an item at all, there's nothing to compare to what's wanted.
+        initial_state.remove("has_item");
+        assert_eq!(plan(&initial_state, &goal_state, &actions), None);
+    }
+
+    #[test]
+    fn test_plan_cost() {
+        let mut cheap = Action::new("cheap".to_string(), 1);
+        cheap.post_conditions.insert("has_key".to_string(), true.into());
+
+        let mut unlock = Action::new("unlock".to_string(), 2);
+        unlock.pre_conditions.insert("has_key".to_string(), true.into());
+        unlock.post_conditions.insert("door_open".to_string(), true.into());
+
+        let mut smash = Action::new("smash".to_string(), 5);
+        smash.post_conditions.insert("door_open".to_string(), true.into());
+
+        let actions = [cheap, unlock, smash];
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_key".to_string(), false.into());
+        initial_state.insert("door_open".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("door_open".to_string(), true.into());
+
+        let planned = plan_with_cost(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(planned.actions.len(), 2);
+        assert_eq!(planned.cost, 3);
+        assert_eq!(planned.steps(), vec![(&actions[0], 1), (&actions[1], 2)]);
+
+        // Nothing to do, nothing to pay.
+        let planned = plan_with_cost(&initial_state, &initial_state, &actions).unwrap();
+        assert!(planned.actions.is_empty());
+        assert_eq!(planned.cost, 0);
+    }
+
+    #[test]
+    fn test_plan_in_context() {
+        struct World {
+            enemy_distance: f64,
+        }
+
+        let actions = [Action::generic("stab", 1)
+                           .checks(|world: &World| world.enemy_distance < 2.0)
+                           .produces("enemy_dead", true),
+                       Action::generic("shoot", 3)
+                           .requires("has_gun", true)
+                           .produces("enemy_dead", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_gun".to_string(), true.into());
+        initial_state.insert("enemy_dead".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("enemy_dead".to_string(), true.into());
+
+        // Stabbing is cheaper, but only possible when the enemy is close enough.
+        let near = World { enemy_distance: 1.0 };
+        let plan = plan_in_context(&initial_state, &goal_state, &actions, &near).unwrap();
+        assert_eq!(plan[0].name, "stab");
+
+        let far = World { enemy_distance: 10.0 };
+        let plan = plan_in_context(&initial_state, &goal_state, &actions, &far).unwrap();
+        assert_eq!(plan[0].name, "shoot");
+
+        initial_state.insert("has_gun".to_string(), false.into());
+        assert_eq!(plan_in_context(&initial_state, &goal_state, &actions, &far), None);
+    }
+
+    #[test]
+    fn test_costs_in_context() {
+        struct World {
+            enemy_distance: usize,
+        }
+
+        // Charging costs more the farther away the enemy is.
+        let actions = [Action::generic("charge", 1)
+                           .costs_in_context(|_, world: &World| 1 + world.enemy_distance)
+                           .produces("enemy_dead", true),
+                       Action::generic("shoot", 5).produces("enemy_dead", true)];
+
+        let initial_state = State::new();
+        let mut goal_state = State::new();
+        goal_state.insert("enemy_dead".to_string(), true.into());
+
+        let near = World { enemy_distance: 2 };
+        let plan = plan_in_context(&initial_state, &goal_state, &actions, &near).unwrap();
+        assert_eq!(plan[0].name, "charge");
+
+        let far = World { enemy_distance: 8 };
+        let plan = plan_in_context(&initial_state, &goal_state, &actions, &far).unwrap();
+        assert_eq!(plan[0].name, "shoot");
+    }
+
+    #[test]
+    fn test_dynamic_costs() {
+        // Walking gets more expensive the farther the shop is.
+        let actions = [Action::new("walk_to_shop", 1)
+                           .costs(|state| match state.get("shop_distance") {
+                               Some(&Value::Int(distance)) => distance as usize,
+                               _ => 1,
+                           })
+                           .produces("at_shop", true),
+                       Action::new("take_cab", 5).produces("at_shop", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("at_shop".to_string(), false.into());
+        initial_state.insert("shop_distance".to_string(), 2.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("at_shop".to_string(), true.into());
+
+        let planned = plan_with_cost(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(planned.names(), vec!["walk_to_shop"]);
+        assert_eq!(planned.cost, 2);
+        assert_eq!(planned.step_costs, vec![2]);
+        assert_eq!(planned.steps(), vec![(&actions[0], 2)]);
+        assert_eq!(actions[0].cost, 1);
+
+        initial_state.insert("shop_distance".to_string(), 20.into());
+        let planned = plan_with_cost(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(planned.names(), vec!["take_cab"]);
+        assert_eq!(planned.cost, 5);
+    }
+
+    #[test]
+    fn test_plan_with_trace() {
+        let actions = [Action::new("get_key", 1).produces("has_key", true),
+                       Action::new("open_door", 1)
+                           .requires("has_key", true)
+                           .produces("door_open", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_key".to_string(), false.into());
+        initial_state.insert("door_open".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("door_open".to_string(), true.into());
+
+        let mut events = vec![];
+        let planned_actions =
+            plan_with_trace(&initial_state, &goal_state, &actions, |event| events.push(event));
+        assert_eq!(planned_actions, plan(&initial_state, &goal_state, &actions));
+
+        let mut key_state = initial_state.clone();
+        key_state.insert("has_key".to_string(), true.into());
+        assert_eq!(events,
+                   vec![TraceEvent::Expanded {
+                            state: initial_state.clone(),
+                            g_cost: 0,
+                            h_cost: 1,
+                        },
+                        TraceEvent::Applied { action_name: "get_key" },
+                        TraceEvent::Expanded {
+                            state: key_state,
+                            g_cost: 1,
+                            h_cost: 1,
+                        },
+                        TraceEvent::Applied { action_name: "get_key" },
+                        TraceEvent::Applied { action_name: "open_door" }]);
+
+        // When the goal state is already reached, there's no search at all.
+        events.clear();
+        let planned_actions =
+            plan_with_trace(&initial_state, &initial_state, &actions, |event| events.push(event));
+        assert_eq!(planned_actions, Some(vec![]));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_plan_best_effort() {
+        let (mut initial_state, mut goal_state, mut actions) = key_and_door();
+        actions.push(Action::new("climb_wall", 1)
+            .requires("has_ladder", true)
+            .produces("over_wall", true));
+        initial_state.insert("has_ladder".to_string(), false.into());
+        initial_state.insert("over_wall".to_string(), false.into());
+
+        // The door can be opened, but there's no way over the wall.
+        goal_state.insert("over_wall".to_string(), true.into());
+        assert_eq!(plan(&initial_state, &goal_state, &actions), None);
+
+        let (planned_actions, final_state) =
+            plan_best_effort(&initial_state, &goal_state, &actions, 100);
+        let names: Vec<&str> = planned_actions.iter().map(|action| action.name.as_str()).collect();
+        assert_eq!(names, vec!["get_key", "open_door"]);
+        assert_eq!(final_state["door_open"], Value::Bool(true));
+        assert_eq!(final_state["over_wall"], Value::Bool(false));
 
-use std::collections::BTreeMap;
-use std::hash::{Hash, Hasher};
-use pathfinding::prelude::astar;
+        // When the goal state can be reached, the plan is the optimal one.
+        goal_state.remove("over_wall");
+        let (planned_actions, _) = plan_best_effort(&initial_state, &goal_state, &actions, 100);
+        assert_eq!(Some(planned_actions), plan(&initial_state, &goal_state, &actions));
 
-/// A map of state atoms to their values.
-pub type State = BTreeMap<String, bool>;
+        // Without expanding any node, the best that can be done is staying in the initial state.
+        let (planned_actions, final_state) =
+            plan_best_effort(&initial_state, &goal_state, &actions, 0);
+        assert!(planned_actions.is_empty());
+        assert_eq!(final_state, initial_state);
+    }
 
-/// An action that can be used to influence the world state.
-#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
-#[derive(PartialEq, Eq)]
-pub struct Action {
-    pub name: String,
-    pub cost: usize,
-    pub pre_conditions: State,
-    pub post_conditions: State,
-}
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_plan_best_effort_with_timeout() {
+        let (mut initial_state, mut goal_state, actions) = key_and_door();
+        initial_state.insert("over_wall".to_string(), false.into());
+        goal_state.insert("over_wall".to_string(), true.into());
 
-impl Action {
-    pub fn new(name: String, cost: usize) -> Action {
-        Action {
-            name: name,
-            cost: cost,
-            pre_conditions: State::new(),
-            post_conditions: State::new(),
+        // With time to spare, the plan is the one found by expanding every node.
+        let timeout = Duration::new(60, 0);
+        assert_eq!(plan_best_effort_with_timeout(&initial_state, &goal_state, &actions, timeout),
+                   plan_best_effort(&initial_state, &goal_state, &actions, usize::MAX));
+
+        // The clock is looked at before expanding the first node.
+        let timeout = Duration::new(0, 0);
+        let (planned_actions, final_state) =
+            plan_best_effort_with_timeout(&initial_state, &goal_state, &actions, timeout);
+        assert!(planned_actions.is_empty());
+        assert_eq!(final_state, initial_state);
+    }
+
+    #[test]
+    fn test_generic_atoms() {
+        #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+        enum Fact {
+            HasKey,
+            DoorOpen,
         }
+
+        let actions = [Action::generic("get_key", 1).produces(Fact::HasKey, true),
+                       Action::generic("open_door", 1)
+                           .requires(Fact::HasKey, true)
+                           .produces(Fact::DoorOpen, true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert(Fact::HasKey, false.into());
+        initial_state.insert(Fact::DoorOpen, false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert(Fact::DoorOpen, true.into());
+
+        let planned_actions = plan(&initial_state, &goal_state, &actions).unwrap();
+        let names: Vec<&str> = planned_actions.iter().map(|action| action.name.as_str()).collect();
+        assert_eq!(names, vec!["get_key", "open_door"]);
+
+        // Errors tell which atom is at fault in terms of the atom type.
+        let actions = [Action::generic("get_key", 1).produces(Fact::HasKey, true)];
+        initial_state.remove(&Fact::DoorOpen);
+        assert_eq!(try_plan(&initial_state, &goal_state, &actions),
+                   Err(PlanError::GoalAtomNeverProduced(Fact::DoorOpen)));
     }
-}
 
-/// A node in the planner graph.
-#[derive(PartialEq, Eq, Clone)]
-struct PlanNode<'a> {
-    current_state: State,
-    action: Option<&'a Action>,
-}
+    #[test]
+    fn test_plan_with_forbidden() {
+        // Burning the door down is the cheapest way in, but sets the house on fire on the way.
+        let actions = [Action::new("burn_door", 1)
+                           .produces("on_fire", true)
+                           .produces("door_open", true),
+                       Action::new("extinguish", 1)
+                           .requires("on_fire", true)
+                           .produces("on_fire", false),
+                       Action::new("pick_lock", 5).produces("door_open", true)];
 
-impl<'a> Hash for PlanNode<'a> {
-    fn hash<H>(&self, state: &mut H)
-        where H: Hasher
-    {
-        if let Some(action) = self.action {
-            action.name.hash(state);
+        let mut initial_state = State::new();
+        initial_state.insert("on_fire".to_string(), false.into());
+        initial_state.insert("door_open".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("on_fire".to_string(), false.into());
+        goal_state.insert("door_open".to_string(), true.into());
+
+        let names = |actions: Vec<&Action>| -> Vec<String> {
+            actions.iter().map(|action| action.name.clone()).collect()
+        };
+        let planned_actions = plan(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(names(planned_actions), vec!["burn_door", "extinguish"]);
+
+        let mut on_fire = State::new();
+        on_fire.insert("on_fire".to_string(), true.into());
+        let planned_actions =
+            plan_with_forbidden(&initial_state, &goal_state, &actions, &[on_fire]).unwrap();
+        assert_eq!(names(planned_actions), vec!["pick_lock"]);
+    }
+
+    #[test]
+    fn test_plan_with_invariants() {
+        // Swimming across is the cheapest way over, but the diver runs out of air on the way.
+        let actions = [Action::new("swim_across", 1)
+                           .produces("has_oxygen", false)
+                           .produces("across", true),
+                       Action::new("breathe", 1)
+                           .requires("has_oxygen", false)
+                           .produces("has_oxygen", true),
+                       Action::new("take_bridge", 5).produces("across", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_oxygen".to_string(), true.into());
+        initial_state.insert("across".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("has_oxygen".to_string(), true.into());
+        goal_state.insert("across".to_string(), true.into());
+
+        let names = |actions: Vec<&Action>| -> Vec<String> {
+            actions.iter().map(|action| action.name.clone()).collect()
+        };
+        let planned_actions = plan(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(names(planned_actions), vec!["swim_across", "breathe"]);
+
+        let mut has_oxygen = State::new();
+        has_oxygen.insert("has_oxygen".to_string(), true.into());
+        let planned_actions =
+            plan_with_invariants(&initial_state, &goal_state, &actions, &has_oxygen).unwrap();
+        assert_eq!(names(planned_actions), vec!["take_bridge"]);
+
+        // It's too late when the invariants don't even hold to begin with.
+        initial_state.insert("has_oxygen".to_string(), false.into());
+        assert_eq!(plan_with_invariants(&initial_state, &goal_state, &actions, &has_oxygen),
+                   None);
+    }
+
+    #[test]
+    fn test_plan_partial_order() {
+        // Wood and water can be gathered at the same time, but the axe has to be used up first.
+        let actions = [Action::new("get_axe", 1).produces("has_axe", true),
+                       Action::new("chop_wood", 1)
+                           .requires("has_axe", true)
+                           .produces("wood", Effect::Add(1)),
+                       Action::new("drop_axe", 1)
+                           .requires("wood", 1)
+                           .produces("has_axe", false),
+                       Action::new("get_bucket", 1).produces("has_bucket", true),
+                       Action::new("fetch_water", 1)
+                           .requires("has_bucket", true)
+                           .produces("water", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_axe".to_string(), false.into());
+        initial_state.insert("has_bucket".to_string(), false.into());
+        initial_state.insert("wood".to_string(), 0.into());
+        initial_state.insert("water".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("has_axe".to_string(), false.into());
+        goal_state.insert("wood".to_string(), 1.into());
+        goal_state.insert("water".to_string(), true.into());
+
+        let steps = plan_partial_order(&initial_state, &goal_state, &actions).unwrap();
+        let names: Vec<&str> = steps.iter().map(|step| step.0.name.as_str()).collect();
+        assert_eq!(names, vec!["get_axe", "chop_wood", "drop_axe", "get_bucket", "fetch_water"]);
+        let dependencies: Vec<Vec<usize>> = steps.into_iter().map(|step| step.1).collect();
+        assert_eq!(dependencies, vec![vec![], vec![0], vec![0, 1], vec![], vec![3]]);
+
+        // Applying the actions in any order following the dependencies gets to the same state.
+        let mut ordered_state = initial_state.clone();
+        execute(&actions.iter().collect::<Vec<_>>(), &mut ordered_state).unwrap();
+        let mut concurrent_state = initial_state.clone();
+        execute(&[&actions[3], &actions[0], &actions[4], &actions[1], &actions[2]],
+                &mut concurrent_state)
+            .unwrap();
+        assert_eq!(concurrent_state, ordered_state);
+    }
+
+    #[test]
+    fn test_plan_display() {
+        let walk = Action::new("walk", 1);
+        let run = Action::new("run", 2);
+
+        let planned = Plan {
+            actions: vec![&walk, &run],
+            step_costs: vec![1, 2],
+            cost: 3,
+        };
+        assert_eq!(planned.names(), vec!["walk", "run"]);
+        assert_eq!(planned.to_string(), "walk -> run (cost 3)");
+        assert_eq!(format!("{:?}", planned), "walk -> run (cost 3)");
+
+        let record = planned.to_record();
+        assert_eq!(record.steps,
+                   vec![PlanStep {
+                            name: "walk".to_string(),
+                            cost: 1,
+                            total_cost: 1,
+                        },
+                        PlanStep {
+                            name: "run".to_string(),
+                            cost: 2,
+                            total_cost: 3,
+                        }]);
+        assert_eq!(record.cost, 3);
+
+        let planned: Plan = Plan {
+            actions: vec![],
+            step_costs: vec![],
+            cost: 0,
+        };
+        assert_eq!(planned.to_string(), "no actions (cost 0)");
+    }
+
+    #[test]
+    fn test_plan_owned() {
+        let planned = {
+            let actions = vec![Action::new("get_key", 1).produces("has_key", true),
+                               Action::new("open_door", 1)
+                                   .costs(|_| 2)
+                                   .requires("has_key", true)
+                                   .produces("door_open", true)];
+
+            let mut initial_state = State::new();
+            initial_state.insert("has_key".to_string(), false.into());
+
+            let mut goal_state = State::new();
+            goal_state.insert("door_open".to_string(), true.into());
+
+            plan_owned(&initial_state, &goal_state, &actions).unwrap()
+        };
+
+        // The planned actions are still around once the allowed actions are gone.
+        let names: Vec<&str> = planned.iter().map(|action| action.name.as_str()).collect();
+        assert_eq!(names, vec!["get_key", "open_door"]);
+
+        // Clones share the cost functions of the actions they're cloned from.
+        let open_door = planned[1].clone();
+        assert_eq!(open_door, planned[1]);
+        assert_eq!((open_door.cost_fn.unwrap())(&State::new(), &()), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_plan_with_max_len() {
+        // Getting the key first is cheaper, but takes more actions than smashing the door.
+        let actions = [Action::new("find_key", 1).produces("has_key", true),
+                       Action::new("get_key", 1)
+                           .requires("has_key", true)
+                           .produces("holds_key", true),
+                       Action::new("open_door", 1)
+                           .requires("holds_key", true)
+                           .produces("door_open", true),
+                       Action::new("smash_door", 5).produces("door_open", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_key".to_string(), false.into());
+        initial_state.insert("holds_key".to_string(), false.into());
+        initial_state.insert("door_open".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("door_open".to_string(), true.into());
+
+        let names = |planned: Option<Vec<&Action>>| -> Option<Vec<String>> {
+            planned.map(|actions| actions.iter().map(|action| action.name.clone()).collect())
+        };
+        assert_eq!(names(plan_with_max_len(&initial_state, &goal_state, &actions, 3)),
+                   names(plan(&initial_state, &goal_state, &actions)));
+        assert_eq!(names(plan_with_max_len(&initial_state, &goal_state, &actions, 2)),
+                   Some(vec!["smash_door".to_string()]));
+
+        // Without smashing, the shortest plan doesn't fit in the limit.
+        assert_eq!(plan_with_max_len(&initial_state, &goal_state, &actions[..3], 2), None);
+        assert_eq!(plan_with_max_len(&initial_state, &initial_state, &actions, 0),
+                   Some(vec![]));
+    }
+
+    #[test]
+    fn test_plan_with_cost_overrides() {
+        let (initial_state, goal_state, actions) = key_and_door();
+        let names = |actions: &[Action], overrides: &BTreeMap<String, usize>| -> Vec<String> {
+            plan_with_cost_overrides(&initial_state, &goal_state, actions, overrides)
+                .unwrap()
+                .iter()
+                .map(|action| action.name.clone())
+                .collect()
+        };
+        let mut overrides = BTreeMap::new();
+        assert_eq!(names(&actions, &overrides), vec!["get_key", "open_door"]);
+
+        // With the key far away, smashing the door becomes cheaper, for this plan only.
+        overrides.insert("get_key".to_string(), 10);
+        assert_eq!(names(&actions, &overrides), vec!["smash_door"]);
+        assert_eq!(actions[0].cost, 1);
+
+        // Cost functions are overridden too.
+        let actions = [actions[0].clone().costs(|_| 10), actions[1].clone(), actions[2].clone()];
+        overrides.insert("get_key".to_string(), 1);
+        assert_eq!(names(&actions, &overrides), vec!["get_key", "open_door"]);
+    }
+
+    #[test]
+    fn test_plan_within_budget() {
+        // Getting the key then opening the door costs 21, smashing it costs 30.
+        let actions = [Action::new("get_key", 7).produces("has_key", true),
+                       Action::new("unlock_door", 7)
+                           .requires("has_key", true)
+                           .produces("door_unlocked", true),
+                       Action::new("open_door", 7)
+                           .requires("door_unlocked", true)
+                           .produces("door_open", true),
+                       Action::new("smash_door", 30).produces("door_open", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_key".to_string(), false.into());
+        initial_state.insert("door_unlocked".to_string(), false.into());
+        initial_state.insert("door_open".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("door_open".to_string(), true.into());
+
+        assert_eq!(plan_within_budget(&initial_state, &goal_state, &actions, 21),
+                   plan(&initial_state, &goal_state, &actions));
+        assert_eq!(plan_within_budget(&initial_state, &goal_state, &actions, 20), None);
+        assert_eq!(plan_within_budget(&initial_state, &initial_state, &actions, 0),
+                   Some(vec![]));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_is_reachable() {
+        let (initial_state, mut goal_state, actions) = key_and_door();
+        assert!(is_reachable(&initial_state, &goal_state, &actions));
+        assert!(is_reachable(&initial_state, &initial_state, &actions));
+
+        // No action opens the window.
+        goal_state.insert("window_open".to_string(), true.into());
+        assert!(!is_reachable(&initial_state, &goal_state, &actions));
+        assert!(!is_reachable(&initial_state, &goal_state, &[]));
+    }
+
+    #[test]
+    fn test_reachable_states() {
+        // With the door harder to open, reaching it with the key costs 3.
+        let (initial_state, _, mut actions) = key_and_door();
+        actions[1].cost = 2;
+
+        let state = |has_key: bool, door_open: bool| {
+            let mut state = State::new();
+            state.insert("has_key".to_string(), has_key.into());
+            state.insert("door_open".to_string(), door_open.into());
+            state
+        };
+        assert_eq!(reachable_states(&initial_state, &actions, 3),
+                   vec![(state(false, false), 0), (state(true, false), 1), (state(true, true), 3)]);
+        assert_eq!(reachable_states(&initial_state, &actions, 5),
+                   vec![(state(false, false), 0),
+                        (state(true, false), 1),
+                        (state(true, true), 3),
+                        (state(false, true), 5)]);
+        assert_eq!(reachable_states(&initial_state, &actions, 0), vec![(initial_state, 0)]);
+    }
+
+    #[test]
+    fn test_plan_owned_states() {
+        let (initial_state, goal_state, actions) = key_and_door();
+        let planned = plan_owned_states(initial_state, goal_state, &actions);
+        assert_eq!(planned, Some(vec![&actions[0], &actions[1]]));
+    }
+
+    #[test]
+    fn test_plan_indices() {
+        // The indices point to the same actions as `plan` plans.
+        for seed in 0..20 {
+            let (initial_state, goal_state, actions) = generate_domain(12, 40, seed);
+            let indices = plan_indices(&initial_state, &goal_state, &actions);
+            let planned = indices.map(|indices| {
+                indices.into_iter().map(|index| &actions[index]).collect::<Vec<_>>()
+            });
+            assert_eq!(planned, plan(&initial_state, &goal_state, &actions));
         }
 
-        for (key, value) in &self.current_state {
-            key.hash(state);
-            value.hash(state);
+        let mut actions = vec![Action::new("open_door", 1)
+                                   .requires("has_key", true)
+                                   .produces("door_open", true),
+                               Action::new("get_key", 1).produces("has_key", true)];
+        let initial_state = state([("has_key", false)]);
+        let goal_state = state([("door_open", true)]);
+        let indices = plan_indices(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(indices, vec![1, 0]);
+
+        // The actions aren't borrowed by the plan, so they can change while it's kept.
+        actions[indices[0]].cost = 2;
+        assert_eq!(actions[1].cost, 2);
+    }
+
+    #[test]
+    fn test_plan_with_final_state() {
+        let actions = [Action::new("get_key", 1).produces("has_key", true),
+                       Action::new("open_door", 1)
+                           .requires("has_key", true)
+                           .produces("door_open", true)
+                           .produces("door_creaked", true),
+                       Action::new("sit_down", 1).produces("sitting", true)];
+        let initial_state = state([("has_key", false), ("door_open", false)]);
+        let goal_state = state([("door_open", true)]);
+
+        // The final state is the one executing the plan leads to, with every effect applied.
+        let (planned, final_state) =
+            plan_with_final_state(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(Some(planned.clone()), plan(&initial_state, &goal_state, &actions));
+        assert!(goal_state.iter().all(|(name, value)| final_state[name] == *value));
+        let mut executed = initial_state.clone();
+        for action in &planned {
+            apply(action, &mut executed);
         }
+        assert_eq!(final_state, executed);
+        assert_eq!(final_state,
+                   state([("has_key", true), ("door_open", true), ("door_creaked", true)]));
+
+        assert_eq!(plan_with_final_state(&initial_state, &initial_state, &actions),
+                   Some((vec![], initial_state.clone())));
+        let goal_state = state([("has_key", false), ("door_open", true)]);
+        assert_eq!(plan_with_final_state(&initial_state, &goal_state, &actions), None);
     }
-}
 
-impl<'a> PlanNode<'a> {
-    /// Makes an initial plan node without a parent.
-    fn initial(initial_state: &'a State) -> PlanNode<'a> {
-        PlanNode {
-            current_state: initial_state.clone(),
-            action: None,
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_plan_arc() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Action>();
+        assert_send_sync::<Planner>();
+
+        let actions: Arc<[Action]> = vec![Action::new("get_key", 1).produces("has_key", true),
+                                          Action::new("open_door", 1)
+                                              .costs(|_| 1)
+                                              .requires("has_key", true)
+                                              .produces("door_open", true)]
+            .into();
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let actions = actions.clone();
+                std::thread::spawn(move || {
+                    let mut initial_state = State::new();
+                    initial_state.insert("has_key".to_string(), false.into());
+
+                    let mut goal_state = State::new();
+                    goal_state.insert("door_open".to_string(), true.into());
+
+                    plan_arc(&initial_state, &goal_state, actions).unwrap().len()
+                })
+            })
+            .collect();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), 2);
         }
     }
 
-    /// Makes a plan node from a parent state and an action applied to that state.
-    fn child(parent_state: State, action: &'a Action) -> PlanNode<'a> {
-        let mut child = PlanNode {
-            current_state: parent_state.clone(),
-            action: Some(action),
+    #[test]
+    fn test_once_actions() {
+        // Drinking the potion twice would be the cheapest, but there's only one potion.
+        let mut actions = [Action::new("drink_potion", 1).once().produces("health", Effect::Add(1)),
+                           Action::new("rest", 3).produces("health", Effect::Add(1))];
+
+        let mut initial_state = State::new();
+        initial_state.insert("health".to_string(), 0.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("health".to_string(), 2.into());
+
+        let planned = plan_with_cost(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(planned.cost, 4);
+        assert_eq!(planned.actions.iter().filter(|action| action.once).count(), 1);
+
+        // A single potion is all it takes to get a single point of health.
+        goal_state.insert("health".to_string(), 1.into());
+        assert_eq!(plan(&initial_state, &goal_state, &actions), Some(vec![&actions[0]]));
+
+        goal_state.insert("health".to_string(), 2.into());
+        actions[0].once = false;
+        let planned = plan_with_cost(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(planned.names(), vec!["drink_potion", "drink_potion"]);
+    }
+
+    #[test]
+    fn test_grouped_actions() {
+        // Picking up both weapons would be the cheapest, but only one weapon can be carried.
+        let mut actions = [Action::new("pick_sword", 1)
+                               .in_group("weapon")
+                               .produces("has_sword", true),
+                           Action::new("pick_hammer", 1)
+                               .in_group("weapon")
+                               .produces("has_hammer", true),
+                           Action::new("slay_enemy", 1)
+                               .requires("has_sword", true)
+                               .produces("enemy_dead", true),
+                           Action::new("smash_door", 1)
+                               .requires("has_hammer", true)
+                               .produces("door_open", true),
+                           Action::new("kick_door", 5).produces("door_open", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_sword".to_string(), false.into());
+        initial_state.insert("has_hammer".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("enemy_dead".to_string(), true.into());
+        goal_state.insert("door_open".to_string(), true.into());
+
+        let planned = plan_with_cost(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(planned.cost, 7);
+        assert_eq!(planned.names(), vec!["pick_sword", "slay_enemy", "kick_door"]);
+
+        // Actions of different groups don't get in the way of each other.
+        actions[1].group = Some("tool".to_string());
+        let planned = plan_with_cost(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(planned.cost, 4);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_plan_with_real_costs() {
+        // The road is shorter, but going through the forest is a bit faster.
+        let actions = [Action::new("take_road", 1).produces("in_town", true),
+                       Action::new("enter_forest", 1).produces("in_forest", true),
+                       Action::new("leave_forest", 1)
+                           .requires("in_forest", true)
+                           .produces("in_town", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("in_forest".to_string(), false.into());
+        initial_state.insert("in_town".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("in_town".to_string(), true.into());
+
+        let distances = |action: &Action, _: &State| match action.name.as_str() {
+            "take_road" => 2.5,
+            "enter_forest" => 1.25,
+            _ => 1.0,
         };
+        let (planned, cost) =
+            plan_with_real_costs(&initial_state, &goal_state, &actions, distances).unwrap();
+        let names: Vec<&str> = planned.iter().map(|action| action.name.as_str()).collect();
+        assert_eq!(names, vec!["enter_forest", "leave_forest"]);
+        assert_eq!(cost, 2.25);
 
-        // Applies the post-condition of the action applied on our parent state.
-        for (name, value) in &action.post_conditions {
-            child.current_state.insert(name.clone(), value.clone());
-        }
+        let (planned, cost) =
+            plan_with_real_costs(&initial_state, &goal_state, &actions, |_, _| 1.5).unwrap();
+        assert_eq!(planned, vec![&actions[0]]);
+        assert_eq!(cost, 1.5);
+
+        assert_eq!(plan_with_real_costs(&initial_state, &initial_state, &actions, distances),
+                   Some((vec![], 0.0)));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_plan_with_stats() {
+        let (initial_state, goal_state, actions) = key_and_door();
+
+        let (planned, stats) = plan_with_stats(&initial_state, &goal_state, &actions);
+        assert_eq!(planned, plan(&initial_state, &goal_state, &actions));
+        assert_eq!(stats,
+                   PlanStats {
+                       expanded: 2,
+                       generated: 5,
+                       peak_open: 2,
+                       solution_depth: 2,
+                       max_depth: 1,
+                   });
+        let factor = stats.effective_branching_factor();
+        assert!((factor + factor * factor - 5.0).abs() < 1e-9);
 
-        child
+        let (planned, stats) = plan_with_stats(&initial_state, &initial_state, &actions);
+        assert_eq!(planned, Some(vec![]));
+        assert_eq!(stats,
+                   PlanStats {
+                       expanded: 0,
+                       generated: 0,
+                       peak_open: 1,
+                       solution_depth: 0,
+                       max_depth: 0,
+                   });
+        assert_eq!(stats.effective_branching_factor(), 0.0);
     }
 
-    /// Returns all possible nodes from this current state, along with the cost to get there.
-    fn possible_next_nodes(&self, actions: &'a [Action]) -> Vec<(PlanNode<'a>, usize)> {
-        let mut nodes: Vec<(PlanNode<'a>, usize)> = vec![];
-        for action in actions {
-            if self.matches(&action.pre_conditions) {
-                nodes.push((PlanNode::child(self.current_state.clone(), action), action.cost));
+    #[test]
+    fn test_deterministic_plans() {
+        // Every pair of a tool and a material makes a usable item, all equally cheap.
+        let mut actions = vec![];
+        for tool in &["axe", "hammer", "knife", "saw"] {
+            actions.push(Action::new(format!("get_{}", tool), 1).produces(*tool, true));
+            for material in &["bone", "stone", "wood"] {
+                actions.push(Action::new(format!("get_{}", material), 1).produces(*material, true));
+                actions.push(Action::new(format!("craft_with_{}_{}", tool, material), 1)
+                    .requires(*tool, true)
+                    .requires(*material, true)
+                    .produces("has_item", true));
             }
         }
 
-        nodes
+        let initial_state = State::new();
+        let mut goal_state = State::new();
+        goal_state.insert("has_item".to_string(), true.into());
+
+        let names = |actions: &[Action]| -> Vec<String> {
+            plan(&initial_state, &goal_state, actions)
+                .unwrap()
+                .iter()
+                .map(|action| action.name.clone())
+                .collect()
+        };
+        let expected = names(&actions);
+        assert_eq!(expected, vec!["get_axe", "get_bone", "craft_with_axe_bone"]);
+        for _ in 0..50 {
+            assert_eq!(names(&actions), expected);
+        }
+
+        // The order the actions are given in doesn't matter either.
+        actions.reverse();
+        assert_eq!(names(&actions), expected);
+        actions.sort_by_key(|action| action.name.len());
+        assert_eq!(names(&actions), expected);
     }
 
-    /// Count the number of states in this node that aren't matching the given target.
-    fn mismatch_count(&self, target: &State) -> usize {
-        let mut count: usize = 0;
-        for (name, target_value) in target {
-            if let Some(current_value) = self.current_state.get(name) {
-                if current_value != target_value {
-                    count += 1;
-                }
-            } else {
-                count += 1;
+    #[test]
+    fn test_same_state_tie_order() {
+        // Walking and strolling lead to the same state for the same cost, so that the search can't
+        // tell them apart, and the first one by name is planned whatever the order of the actions.
+        let mut actions = vec![Action::new("walk_to_door", 1).produces("at_door", true),
+                               Action::new("stroll_to_door", 1).produces("at_door", true),
+                               Action::new("open_door", 1)
+                                   .requires("at_door", true)
+                                   .produces("door_open", true)];
+        let mut goal_state = State::new();
+        goal_state.insert("door_open".to_string(), true.into());
+
+        let expected = vec!["stroll_to_door", "open_door"];
+        assert_eq!(plan_with_cost(&State::new(), &goal_state, &actions).unwrap().names(), expected);
+        actions.reverse();
+        assert_eq!(plan_with_cost(&State::new(), &goal_state, &actions).unwrap().names(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_plan_determinism() {
+        use std::ptr;
+
+        // Four ways in, two of which have the same names, all costing 2.
+        let actions = [Action::new("walk_to_door", 1).produces("at_door", true),
+                       Action::new("step_in", 1)
+                           .requires("at_door", true)
+                           .produces("inside", true),
+                       Action::new("climb_in", 2).produces("inside", true),
+                       Action::new("climb_in", 2).produces("inside", true),
+                       Action::new("jump_in", 2).produces("inside", true)];
+        let initial_state = state([("at_door", false), ("inside", false)]);
+        let goal_state = state([("inside", true)]);
+
+        // Each search hashes states with new random keys, as separate runs would, and still
+        // gives the same results.
+        let planned = plan(&initial_state, &goal_state, &actions);
+        let optimal = plan_all_optimal(&initial_state, &goal_state, &actions);
+        assert_eq!(optimal.len(), 4);
+        assert!(ptr::eq(optimal[0][0], &actions[2]) && ptr::eq(optimal[1][0], &actions[3]));
+        let seeded: Vec<_> = (0..8)
+            .map(|seed| {
+                plan_with_tiebreak(&initial_state, &goal_state, &actions, TieBreak::Seeded(seed))
+            })
+            .collect();
+        for _ in 0..50 {
+            assert_eq!(plan(&initial_state, &goal_state, &actions), planned);
+            let again = plan_all_optimal(&initial_state, &goal_state, &actions);
+            assert!(again.iter().zip(&optimal).all(|(again, optimal)| {
+                again.iter().zip(optimal).all(|(&again, &optimal)| ptr::eq(again, optimal))
+            }));
+            for (seed, planned) in seeded.iter().enumerate() {
+                let tie_break = TieBreak::Seeded(seed as u64);
+                assert_eq!(plan_with_tiebreak(&initial_state, &goal_state, &actions, tie_break),
+                           *planned);
             }
         }
 
-        count
+        // Different seeds pick different plans among the optimal ones.
+        assert!(seeded.iter().any(|planned| *planned != seeded[0]));
+        assert!(seeded.iter().all(|planned| optimal.contains(planned.as_ref().unwrap())));
+        assert_eq!(plan_with_tiebreak(&initial_state, &goal_state, &actions[..0],
+                                      TieBreak::Seeded(0)),
+                   None);
     }
 
-    /// Returns `true` if the current node is a full match for the given target.
-    fn matches(&self, target: &State) -> bool {
-        self.mismatch_count(target) == 0
-    }
-}
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_plan_with_tiebreak() {
+        // Both ways in cost 2, but one of them takes a single action.
+        let actions = [Action::new("step_in", 1)
+                           .requires("at_door", true)
+                           .produces("inside", true),
+                       Action::new("walk_to_door", 1).produces("at_door", true),
+                       Action::new("climb_in", 2).produces("inside", true)];
 
-/// Formulates a plan to get from an initial state to a goal state using a set of allowed actions.
-pub fn plan<'a>(initial_state: &'a State,
-                goal_state: &State,
-                allowed_actions: &'a [Action])
-                -> Option<Vec<&'a Action>> {
-    // Builds our initial plan node.
-    let start = PlanNode::initial(initial_state);
+        let mut initial_state = State::new();
+        initial_state.insert("at_door".to_string(), false.into());
+        initial_state.insert("inside".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("inside".to_string(), true.into());
 
-    // Runs our search over the states graph.
-    if let Some((plan, _)) = astar(&start,
-                                   |ref node| node.possible_next_nodes(allowed_actions),
-                                   |ref node| node.mismatch_count(goal_state),
-                                   |ref node| node.matches(goal_state)) {
-        Some(plan.into_iter().skip(1).map(|ref node| node.action.unwrap()).collect())
-    } else {
-        None
+        let names = |tie_break| -> Vec<String> {
+            plan_with_tiebreak(&initial_state, &goal_state, &actions, tie_break)
+                .unwrap()
+                .iter()
+                .map(|action| action.name.clone())
+                .collect()
+        };
+        assert_eq!(names(TieBreak::FewerActions), vec!["climb_in"]);
+        assert_eq!(names(TieBreak::Lexicographic), vec!["climb_in"]);
+
+        // Lexicographic order is only about names, whatever the number of actions.
+        let actions = [Action::new("a_walk_to_door", 1).produces("at_door", true),
+                       Action::new("b_step_in", 1)
+                           .requires("at_door", true)
+                           .produces("inside", true),
+                       Action::new("c_climb_in", 2).produces("inside", true)];
+        let planned = plan_with_tiebreak(&initial_state, &goal_state, &actions,
+                                         TieBreak::Lexicographic);
+        assert_eq!(planned.unwrap().len(), 2);
+        let planned = plan_with_tiebreak(&initial_state, &goal_state, &actions,
+                                         TieBreak::FewerActions);
+        assert_eq!(planned, Some(vec![&actions[2]]));
+        assert_eq!(plan_with_tiebreak(&initial_state, &initial_state, &actions,
+                                      TieBreak::FewerActions),
+                   Some(vec![]));
+
+        // Preferences only choose between plans of the same cost.
+        let actions = [Action::new("sneak_in", 2).produces("inside", true),
+                       Action::new("kick_door_in", 2).preferred(3).produces("inside", true),
+                       Action::new("teleport_in", 5).preferred(10).produces("inside", true)];
+        let planned = plan_with_tiebreak(&initial_state, &goal_state, &actions,
+                                         TieBreak::Preferred);
+        assert_eq!(planned, Some(vec![&actions[1]]));
+        let planned = plan_with_tiebreak(&initial_state, &goal_state, &actions,
+                                         TieBreak::Lexicographic);
+        assert_eq!(planned, Some(vec![&actions[1]]));
+        let actions = [actions[0].clone(), actions[1].clone().preferred(-1)];
+        let planned = plan_with_tiebreak(&initial_state, &goal_state, &actions,
+                                         TieBreak::Preferred);
+        assert_eq!(planned, Some(vec![&actions[0]]));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_plan_with_weights() {
+        let (initial_state, goal_state, actions) = key_and_door();
+
+        // Opening the door takes two actions, so a weight of 2 keeps the plan optimal.
+        let mut weights = BTreeMap::new();
+        weights.insert("door_open".to_string(), 2);
+        let planned = plan_with_weights(&initial_state, &goal_state, &actions, &weights);
+        assert_eq!(planned, plan(&initial_state, &goal_state, &actions));
+
+        // Overestimating the cost left makes the search settle for the first way there.
+        weights.insert("door_open".to_string(), 10);
+        let planned = plan_with_weights(&initial_state, &goal_state, &actions, &weights).unwrap();
+        assert_eq!(planned, vec![&actions[2]]);
+
+        // Atoms with no listed weight weigh 1, as they do with `plan`.
+        let planned = plan_with_weights(&initial_state, &goal_state, &actions, &BTreeMap::new());
+        assert_eq!(planned, plan(&initial_state, &goal_state, &actions));
+    }
 
     #[test]
-    fn test_edge_cases() {
-        let mut action = Action::new("action".to_string(), 1);
-        action.pre_conditions.insert("has_something".to_string(), true);
-        action.post_conditions.insert("is_winning".to_string(), true);
+    #[cfg(feature = "std")]
+    fn test_plan_with_spec() {
+        // Both ways in are as cheap, but only one of them leaves the lights on.
+        let actions = [Action::new("walk_in", 1)
+                           .requires("lights_on", true)
+                           .produces("inside", true)
+                           .produces("lights_on", false),
+                       Action::new("crawl_in", 1).produces("inside", true),
+                       Action::new("jump_in", 2)
+                           .produces("inside", true)
+                           .produces("window_broken", true)];
 
-        let actions = [action];
+        let mut initial_state = State::new();
+        initial_state.insert("inside".to_string(), false.into());
+        initial_state.insert("lights_on".to_string(), true.into());
+        initial_state.insert("window_broken".to_string(), false.into());
+
+        let names = |planned: Option<Vec<&Action>>| -> Vec<String> {
+            planned.unwrap().iter().map(|action| action.name.clone()).collect()
+        };
+
+        let goal_spec = GoalSpec::new().requires("inside", true).prefers("lights_on", true);
+        assert_eq!(names(plan_with_spec(&initial_state, &goal_spec, &actions)), vec!["crawl_in"]);
+        let goal_spec = GoalSpec::new().requires("inside", true).prefers("lights_on", false);
+        assert_eq!(names(plan_with_spec(&initial_state, &goal_spec, &actions)), vec!["walk_in"]);
+
+        // Soft goal atoms never make for a more expensive plan.
+        let goal_spec = GoalSpec::new().requires("inside", true).prefers("window_broken", true);
+        assert_eq!(plan_with_spec(&initial_state, &goal_spec, &actions).unwrap().len(), 1);
+
+        // Elsewhere, only the hard goal atoms are planned toward.
+        assert_eq!(plan(&initial_state, &goal_spec, &actions).unwrap().len(), 1);
+        let goal_spec = GoalSpec::new().requires("outside", true).prefers("inside", true);
+        assert_eq!(plan_with_spec(&initial_state, &goal_spec, &actions), None);
+    }
+
+    #[test]
+    fn test_zero_cost_cycles() {
+        // Switching the lights on and off again is free, and doesn't get anywhere.
+        let actions = [Action::new("switch_on", 0).produces("lights_on", true),
+                       Action::new("switch_off", 0).produces("lights_on", false),
+                       Action::new("open_door", 1)
+                           .requires("lights_on", true)
+                           .produces("door_open", true)];
 
         let mut initial_state = State::new();
-        initial_state.insert("has_something".to_string(), false);
-        initial_state.insert("is_winning".to_string(), false);
+        initial_state.insert("lights_on".to_string(), false.into());
+        initial_state.insert("door_open".to_string(), false.into());
 
-        // No viable plan.
-        {
-            let mut goal_state = State::new();
-            goal_state.insert("is_winning".to_string(), true);
+        let mut goal_state = State::new();
+        goal_state.insert("door_open".to_string(), true.into());
+        let planned = plan_with_cost(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(planned.names(), vec!["switch_on", "open_door"]);
+        assert_eq!(planned.cost, 1);
 
-            let plan = plan(&initial_state, &goal_state, &actions);
-            assert!(plan.is_none());
-        }
+        // The search still runs out of states to go through when the goal is unreachable.
+        goal_state.insert("window_open".to_string(), true.into());
+        assert_eq!(plan(&initial_state, &goal_state, &actions), None);
+        assert_eq!(plan_validated(&initial_state, &goal_state, &actions),
+                   Err(PlanError::InvalidAction("switch_on".to_string(), ActionError::ZeroCost)));
+    }
 
-        // The goal state is already reached in the initial state.
-        {
-            let mut goal_state = State::new();
-            goal_state.insert("is_winning".to_string(), false);
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_plan_backward() {
+        // Regressing from the goal goes through getting the key and opening the door, while
+        // dropping the key and turning the lights on never help fulfilling what's left.
+        let (mut initial_state, mut goal_state, mut actions) = key_and_door();
+        actions.push(Action::new("drop_key", 1).produces("has_key", false));
+        actions.push(Action::new("turn_lights_on", 1).produces("lights_on", true));
+        initial_state.insert("lights_on".to_string(), false.into());
 
-            let plan = plan(&initial_state, &goal_state, &actions);
-            assert!(plan.unwrap().len() == 0);
-        }
+        let planned = plan_backward(&initial_state, &goal_state, &actions);
+        assert_eq!(planned, plan(&initial_state, &goal_state, &actions));
+        assert_eq!(planned.unwrap().len(), 2);
 
-        // The goal state uses a state missing from the initial state.
-        {
-            let mut goal_state = State::new();
-            goal_state.insert("is_losing".to_string(), false);
+        // Actions undoing what later ones require are never planned before them.
+        goal_state.insert("has_key".to_string(), false.into());
+        let planned = plan_backward(&initial_state, &goal_state, &actions);
+        assert_eq!(planned, plan(&initial_state, &goal_state, &actions));
+        assert_eq!(planned.unwrap().len(), 3);
 
-            let plan = plan(&initial_state, &goal_state, &actions);
-            assert!(plan.is_none());
-        }
+        assert_eq!(plan_backward(&initial_state, &initial_state, &actions), Some(vec![]));
+
+        let mut goal_state = State::new();
+        goal_state.insert("window_open".to_string(), true.into());
+        assert_eq!(plan_backward(&initial_state, &goal_state, &actions), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_plan_ida_star() {
+        // Raising the cost bound pass after pass, the search ends on the same cheapest plan as
+        // `plan` does, in which the key is fetched rather than the door smashed.
+        let (initial_state, goal_state, actions) = key_and_door();
+
+        let planned = plan_ida_star(&initial_state, &goal_state, &actions);
+        assert_eq!(planned, plan(&initial_state, &goal_state, &actions));
+        assert_eq!(planned.unwrap().len(), 2);
+
+        assert_eq!(plan_ida_star(&initial_state, &initial_state, &actions), Some(vec![]));
+
+        let mut goal_state = State::new();
+        goal_state.insert("window_open".to_string(), true.into());
+        assert_eq!(plan_ida_star(&initial_state, &goal_state, &actions), None);
+    }
+
+    #[test]
+    fn test_unsetting_effects() {
+        // Food must be eaten before more can be bought, so it has to run out along the way.
+        let actions = [Action::new("eat", 1)
+                           .requires("has_food", true)
+                           .produces("has_food", false)
+                           .produces("hungry", false),
+                       Action::new("buy_food", 1)
+                           .requires("has_food", false)
+                           .produces("has_food", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_food".to_string(), true.into());
+        initial_state.insert("hungry".to_string(), true.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("has_food".to_string(), true.into());
+        goal_state.insert("hungry".to_string(), false.into());
+
+        let steps = plan_with_states(&initial_state, &goal_state, &actions).unwrap();
+        let names: Vec<&str> = steps.iter().map(|&(action, _)| action.name.as_str()).collect();
+        assert_eq!(names, vec!["eat", "buy_food"]);
+        assert_eq!(steps[0].1["has_food"], Value::Bool(false));
+        assert_eq!(steps[0].1["hungry"], Value::Bool(false));
+        assert_eq!(steps[1].1, goal_state);
+
+        // Unsetting an atom can be the goal itself.
+        let mut goal_state = State::new();
+        goal_state.insert("has_food".to_string(), false.into());
+        let planned = plan_with_cost(&initial_state, &goal_state, &actions).unwrap();
+        assert_eq!(planned.names(), vec!["eat"]);
+
+        // Eating is the only way to run out of food, and it always gets rid of hunger.
+        let mut goal_state = State::new();
+        goal_state.insert("hungry".to_string(), true.into());
+        goal_state.insert("has_food".to_string(), false.into());
+        assert_eq!(plan(&initial_state, &goal_state, &actions), None);
     }
 }
 
@@ -269,25 +4433,129 @@ mod tests_with_serde {
 
         /// Checks if the computed plan matches the expectation.
         fn assert_plan(&self) {
-            let plan = plan(&self.initial_state, &self.goal_state, &self.actions);
+            let plan = plan_with_cost(&self.initial_state, &self.goal_state, &self.actions);
 
-            if let Some(actions_list) = plan {
-                let actions_names: Vec<String> =
-                    actions_list.iter().map(|&action| action.name.clone()).collect();
-                if self.expected_actions != actions_names {
-                    panic!("{} failed: expected {:?}, got {:?}",
+            if let Some(plan) = plan {
+                if self.expected_actions != plan.names() {
+                    panic!("{} failed: expected {:?}, got {}",
                            self.case_name,
                            self.expected_actions,
-                           actions_names);
-                }
-            } else {
-                if self.expected_actions.len() > 0 {
-                    panic!("{} failed: expected {:?}, got no plan",
-                           self.case_name,
-                           self.expected_actions);
+                           plan);
                 }
+            } else if !self.expected_actions.is_empty() {
+                panic!("{} failed: expected {:?}, got no plan",
+                       self.case_name,
+                       self.expected_actions);
             }
         }
+
+        /// Checks if the goal state is found to be reachable exactly when there's a plan.
+        fn assert_reachability(&self) {
+            let planned = plan(&self.initial_state, &self.goal_state, &self.actions);
+            assert_eq!(is_reachable(&self.initial_state, &self.goal_state, &self.actions),
+                       planned.is_some(),
+                       "{} failed reachability",
+                       self.case_name);
+        }
+
+        /// Checks if searching backward finds a plan as cheap as the one searching forward finds.
+        fn assert_backward_plan(&self) {
+            let cost = |actions: Vec<&Action>| -> usize {
+                actions.iter().map(|action| action.cost).sum()
+            };
+            let expected = plan(&self.initial_state, &self.goal_state, &self.actions).map(&cost);
+            let planned = plan_backward(&self.initial_state, &self.goal_state, &self.actions)
+                .map(&cost);
+            assert_eq!(planned, expected, "{} failed backward", self.case_name);
+        }
+
+        /// Checks if iterative-deepening A* finds the same plan as A* does.
+        fn assert_ida_star_plan(&self) {
+            let expected = plan(&self.initial_state, &self.goal_state, &self.actions);
+            let planned = plan_ida_star(&self.initial_state, &self.goal_state, &self.actions);
+            assert_eq!(planned, expected, "{} failed with IDA*", self.case_name);
+        }
+    }
+
+    #[test]
+    fn test_value_serialization() {
+        let mut state = State::new();
+        state.insert("alive".to_string(), true.into());
+        state.insert("wood".to_string(), 3.into());
+        state.insert("weapon".to_string(), Value::Enum(3));
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(json, r#"{"alive":true,"weapon":{"enum":3},"wood":3}"#);
+        assert_eq!(serde_json::from_str::<State>(&json).unwrap(), state);
+    }
+
+    #[test]
+    fn test_goal_serialization() {
+        let mut goal = Goal::new();
+        goal.insert("daylight".to_string(), true.into());
+        goal.insert("torch_lit".to_string(), GoalAtom::MustBeAbsent);
+        goal.insert("visitor".to_string(), GoalAtom::Present);
+
+        let json = serde_json::to_string(&goal).unwrap();
+        assert_eq!(json, r#"{"daylight":true,"torch_lit":null,"visitor":"present"}"#);
+        assert_eq!(serde_json::from_str::<Goal>(&json).unwrap(), goal);
+    }
+
+    #[test]
+    fn test_conditions_serialization() {
+        let mut action = Action::new("build".to_string(), 1);
+        action.pre_conditions.insert("wood".to_string(), Condition::GreaterOrEqual(3));
+        action.pre_conditions.insert("has_axe".to_string(), true.into());
+        action.post_conditions.insert("wood".to_string(), Effect::Add(-3));
+        action.post_conditions.insert("has_house".to_string(), true.into());
+        action.post_conditions.insert("lights_on".to_string(), Effect::Toggle);
+
+        let json = serde_json::to_string(&action).unwrap();
+        assert_eq!(json,
+                   r#"{"name":"build","cost":1,"pre_conditions":{"has_axe":true,"wood":{">=":3}},"post_conditions":{"has_house":true,"lights_on":"toggle","wood":{"add":-3}}}"#);
+        assert_eq!(serde_json::from_str::<Action>(&json).unwrap(), action);
+    }
+
+    #[test]
+    fn test_action_metadata_serialization() {
+        let action = Action::new("light_torch", 1)
+            .produces("torch_lit", true)
+            .described("Lights up the torch at hand.")
+            .tagged("fire")
+            .tagged("light");
+        assert!(action.has_tag("fire"));
+        assert!(!action.has_tag("water"));
+
+        let json = serde_json::to_string(&action).unwrap();
+        assert_eq!(json,
+                   r#"{"name":"light_torch","cost":1,"pre_conditions":{},"post_conditions":{"torch_lit":true},"description":"Lights up the torch at hand.","tags":["fire","light"]}"#);
+        assert_eq!(serde_json::from_str::<Action>(&json).unwrap(), action);
+
+        // Metadata is empty when left out.
+        let json = r#"{"name":"light_torch","cost":1,"pre_conditions":{},"post_conditions":{}}"#;
+        let action = serde_json::from_str::<Action>(json).unwrap();
+        assert_eq!(action.description, "");
+        assert!(action.tags.is_empty());
+    }
+
+    #[test]
+    fn test_plan_record_serialization() {
+        let actions = [Action::new("get_key", 1).produces("has_key", true),
+                       Action::new("open_door", 2)
+                           .requires("has_key", true)
+                           .produces("door_open", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_key".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("door_open".to_string(), true.into());
+
+        let record = plan_with_cost(&initial_state, &goal_state, &actions).unwrap().to_record();
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(json,
+                   r#"{"steps":[{"name":"get_key","cost":1,"total_cost":1},{"name":"open_door","cost":2,"total_cost":3}],"cost":3}"#);
+        assert_eq!(serde_json::from_str::<PlanRecord>(&json).unwrap(), record);
     }
 
     #[test]
@@ -296,6 +4564,9 @@ mod tests_with_serde {
         for path in paths {
             let case = TestCase::from_case_file(path.unwrap().path().as_path());
             case.assert_plan();
+            case.assert_ida_star_plan();
+            case.assert_reachability();
+            case.assert_backward_plan();
         }
     }
 }
@@ -0,0 +1,94 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Helpers for states whose atom names are grouped in namespaces, such as `inventory.wood`.
+
+use std::collections::btree_map;
+use std::ops::Bound;
+#[cfg(not(feature = "std"))]
+use prelude::*;
+use {State, Value};
+
+/// The atoms of a state whose names start with a prefix, as returned by `Namespaces::with_prefix`.
+pub struct Prefixed<'a> {
+    range: btree_map::Range<'a, String, Value>,
+    prefix: &'a str,
+}
+
+impl<'a> Iterator for Prefixed<'a> {
+    type Item = (&'a String, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().filter(|&(name, _)| name.starts_with(self.prefix))
+    }
+}
+
+/// Queries and changes to all the atoms of a state under a prefix at once, which is a namespace
+/// when the prefix ends with a separator, such as `inventory.`.
+///
+/// As the atoms of a state are sorted by name, the atoms under a prefix are next to each other, so
+/// they're found without going through the others.
+pub trait Namespaces {
+    /// Returns the atoms whose names start with `prefix`, in order.
+    fn with_prefix<'a>(&'a self, prefix: &'a str) -> Prefixed<'a>;
+
+    /// Removes the atoms whose names start with `prefix`.
+    fn clear_prefix(&mut self, prefix: &str);
+
+    /// Sets atoms named after `prefix` followed by the given names.
+    fn set_prefixed<I, S, V>(&mut self, prefix: &str, atoms: I)
+        where I: IntoIterator<Item = (S, V)>,
+              S: AsRef<str>,
+              V: Into<Value>;
+}
+
+impl Namespaces for State {
+    fn with_prefix<'a>(&'a self, prefix: &'a str) -> Prefixed<'a> {
+        Prefixed {
+            range: self.range::<str, _>((Bound::Included(prefix), Bound::Unbounded)),
+            prefix,
+        }
+    }
+
+    fn clear_prefix(&mut self, prefix: &str) {
+        let names: Vec<String> = self.with_prefix(prefix).map(|(name, _)| name.clone()).collect();
+        for name in names {
+            self.remove(&name);
+        }
+    }
+
+    fn set_prefixed<I, S, V>(&mut self, prefix: &str, atoms: I)
+        where I: IntoIterator<Item = (S, V)>,
+              S: AsRef<str>,
+              V: Into<Value>
+    {
+        for (name, value) in atoms {
+            self.insert(format!("{}{}", prefix, name.as_ref()), value.into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_namespaces() {
+        let mut state = State::new();
+        state.insert("inventory".to_string(), true.into());
+        state.set_prefixed("inventory.", vec![("wood", 3), ("stone", 1)]);
+        state.set_prefixed("location.", vec![("kitchen", true)]);
+        state.insert("location_known".to_string(), true.into());
+
+        let inventory: Vec<_> = state.with_prefix("inventory.").collect();
+        assert_eq!(inventory,
+                   vec![(&"inventory.stone".to_string(), &Value::Int(1)),
+                        (&"inventory.wood".to_string(), &Value::Int(3))]);
+        assert_eq!(state.with_prefix("weather.").count(), 0);
+
+        // Only the atoms under the prefix are cleared, not those whose names merely look alike.
+        state.clear_prefix("location.");
+        let names: Vec<&str> = state.keys().map(|name| name.as_str()).collect();
+        assert_eq!(names,
+                   vec!["inventory", "inventory.stone", "inventory.wood", "location_known"]);
+    }
+}
@@ -0,0 +1,656 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! A planner holding on to its actions across plans.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use search::{AtomId, Changes, FIRST_COUNT_MARK, PlanNode, SearchSpace, path_action_indices};
+use {Action, Atom, Goal, OnMissing, State, ToGoal};
+
+/// A planner for agents which plan over and over with the same set of actions, such as once per
+/// frame.
+///
+/// The last plan formulated is remembered, so that planning again from the same initial state to
+/// the same goal returns it right away, without searching. Planners can be shared between threads,
+/// which can plan concurrently, but share the same last plan.
+///
+/// The actions of a planner can change between plans, such as when an agent picks up an item
+/// enabling new actions. Actions can also be disabled, so that they're left out of plans
+/// while staying around to be enabled again later.
+///
+/// Planners can also remember which actions are applicable to the states they expand, see
+/// `with_applicability_cache`, handle absent atoms differently in goals, see `with_on_missing`,
+/// make actions more expensive each time they're applied again, see `with_repeat_penalty`, and
+/// plan with a single one of equivalent actions, see `with_equivalents_deduped`.
+pub struct Planner<K: Atom = String> {
+    actions: Vec<Action<K>>,
+    enabled: Vec<bool>,
+    last_plan: Mutex<Option<CachedPlan<K>>>,
+    cache_enabled: bool,
+    applicability: Mutex<Option<ApplicabilityCache<K>>>,
+    cache_hits: AtomicUsize,
+    cache_misses: AtomicUsize,
+    on_missing: OnMissing,
+    repeat_penalty: Option<RepeatPenalty>,
+    dedupe_equivalent: bool,
+}
+
+/// The most uses of actions with the same name a repeat penalty escalates over. Further uses cost
+/// as much as the last one, so that the states a search goes through stay in finite number.
+pub const MAX_PENALIZED_USES: usize = 8;
+
+/// An escalating cost for applying actions with the same name several times in a plan, see
+/// `Planner::with_repeat_penalty`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct RepeatPenalty {
+    /// What the cost of an action is multiplied by once for each time an action with the same
+    /// name was applied before it.
+    pub multiplier: usize,
+}
+
+/// How often a planner found which actions are applicable to a state in its applicability cache,
+/// as returned by `Planner::cache_stats`.
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub struct CacheStats {
+    /// The number of states whose applicable actions were found in the cache.
+    pub hits: usize,
+    /// The number of states whose applicable actions had to be checked.
+    pub misses: usize,
+}
+
+/// The actions applicable to the states expanded from an initial state, by the changes making up
+/// each state.
+struct ApplicabilityCache<K> {
+    initial_state: State<K>,
+    applicable: HashMap<Changes, Vec<usize>>,
+}
+
+/// A plan formulated by a planner, along with the inputs it was formulated from.
+struct CachedPlan<K> {
+    initial_state: State<K>,
+    goal: Goal<K>,
+    action_indices: Option<Vec<usize>>,
+}
+
+impl<K: Atom> Planner<K> {
+    /// Makes a planner using the given set of allowed actions.
+    pub fn new(actions: Vec<Action<K>>) -> Planner<K> {
+        Planner {
+            enabled: vec![true; actions.len()],
+            actions,
+            last_plan: Mutex::new(None),
+            cache_enabled: false,
+            applicability: Mutex::new(None),
+            cache_hits: AtomicUsize::new(0),
+            cache_misses: AtomicUsize::new(0),
+            on_missing: OnMissing::CountAsMismatch,
+            repeat_penalty: None,
+            dedupe_equivalent: false,
+        }
+    }
+
+    /// Makes this planner only plan with the first action by name among equivalent ones, and
+    /// returns it.
+    ///
+    /// Equivalent actions have different names, but the same cost, pre-conditions and effects, and
+    /// no cost function, context check, or limit on their uses. Each of them leads to the same
+    /// states, so the search checks and applies all of them for nothing. The plans stay the same,
+    /// as the first one by name is the one planned among actions leading to the same state anyway.
+    /// The others can be found with `equivalent_actions`.
+    pub fn with_equivalents_deduped(mut self) -> Planner<K> {
+        self.dedupe_equivalent = true;
+        self
+    }
+
+    /// Makes this planner multiply the cost of an action by `penalty.multiplier` for each time an
+    /// action with the same name was applied before it in the plan, and returns it.
+    ///
+    /// Plans then favor a variety of actions over applying the cheapest one over and over. This
+    /// makes the same state reached with different uses of the actions different nodes of the
+    /// search, which can explore many more of them, up to `MAX_PENALIZED_USES` uses of each name.
+    pub fn with_repeat_penalty(mut self, penalty: RepeatPenalty) -> Planner<K> {
+        self.repeat_penalty = Some(penalty);
+        self
+    }
+
+    /// Makes this planner handle the goal atoms on atoms absent from the state as told by
+    /// `on_missing`, and returns it.
+    pub fn with_on_missing(mut self, on_missing: OnMissing) -> Planner<K> {
+        self.on_missing = on_missing;
+        self
+    }
+
+    /// Makes this planner remember which actions are applicable to each state it expands, and
+    /// returns it.
+    ///
+    /// The cache is kept for as long as plans are formulated from the same initial state with the
+    /// same actions, so that planning for another goal from there doesn't check the same actions
+    /// against the same states again. A search only expands each state once, so the cache only
+    /// helps across plans.
+    pub fn with_applicability_cache(mut self) -> Planner<K> {
+        self.cache_enabled = true;
+        self
+    }
+
+    /// Returns how often the applicability cache was used so far.
+    pub fn cache_stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.cache_hits.load(Ordering::Relaxed),
+            misses: self.cache_misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the first action with the given name and the enabled actions equivalent to it, in
+    /// order, so that any of them can be applied in its place.
+    pub fn equivalent_actions(&self, name: &str) -> Vec<&Action<K>> {
+        let action = match self.actions.iter().find(|action| action.name == name) {
+            Some(action) => action,
+            None => return vec![],
+        };
+
+        self.actions
+            .iter()
+            .zip(&self.enabled)
+            .filter(|&(other, &enabled)| {
+                enabled && (std::ptr::eq(other, action) || is_equivalent(other, action))
+            })
+            .map(|(other, _)| other)
+            .collect()
+    }
+
+    /// Returns the actions of this planner, whether they're enabled or not.
+    pub fn actions(&self) -> &[Action<K>] {
+        &self.actions
+    }
+
+    /// Adds an enabled action to this planner.
+    pub fn add_action(&mut self, action: Action<K>) {
+        self.actions.push(action);
+        self.enabled.push(true);
+        self.forget_last_plan();
+    }
+
+    /// Removes the first action with the given name from this planner, and returns it, if any.
+    pub fn remove_action(&mut self, name: &str) -> Option<Action<K>> {
+        let index = self.actions.iter().position(|action| action.name == name)?;
+        self.enabled.remove(index);
+        self.forget_last_plan();
+        Some(self.actions.remove(index))
+    }
+
+    /// Enables the actions with the given name, so that they can be planned, and returns `false`
+    /// if there are none.
+    pub fn enable(&mut self, name: &str) -> bool {
+        self.set_enabled(name, true)
+    }
+
+    /// Disables the actions with the given name, so that they're left out of plans, and returns
+    /// `false` if there are none.
+    pub fn disable(&mut self, name: &str) -> bool {
+        self.set_enabled(name, false)
+    }
+
+    /// Returns `true` if there's an enabled action with the given name.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.actions
+            .iter()
+            .zip(&self.enabled)
+            .any(|(action, &enabled)| enabled && action.name == name)
+    }
+
+    /// Enables or disables the actions with the given name, and returns `false` if there are none.
+    fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let mut found = false;
+        for (action, is_enabled) in self.actions.iter().zip(&mut self.enabled) {
+            if action.name == name {
+                *is_enabled = enabled;
+                found = true;
+            }
+        }
+
+        self.forget_last_plan();
+        found
+    }
+
+    /// Forgets the last plan and the applicability cache, once the actions they were made from
+    /// changed.
+    fn forget_last_plan(&mut self) {
+        *self.last_plan.get_mut().unwrap() = None;
+        *self.applicability.get_mut().unwrap() = None;
+    }
+
+    /// Formulates a plan to get from an initial state to a goal state like `plan` does.
+    pub fn plan<G>(&self, initial_state: &State<K>, goal_state: &G) -> Option<Vec<&Action<K>>>
+        where G: ToGoal<K> + ?Sized
+    {
+        let goal = goal_state.to_goal();
+        if let Some(ref cached) = *self.last_plan.lock().unwrap() {
+            if cached.initial_state == *initial_state && cached.goal == goal {
+                return self.actions_at(cached.action_indices.as_ref());
+            }
+        }
+
+        // The last plan isn't locked while searching, so that other threads can still use it.
+        let is_planned = self.planned_actions();
+        let mut space =
+            SearchSpace::filtered(initial_state, &self.actions, &(), |index| is_planned[index]);
+        let search_goal = space.goal(&goal);
+        let on_missing = self.on_missing;
+
+        // The goal atoms the initial state fulfills are left out of the check for goal atoms that
+        // can't be fulfilled, which doesn't know how absent atoms are handled.
+        let checked_goal: Goal<K> = goal.iter()
+            .filter(|&(name, goal_atom)| {
+                !goal_atom.is_met_with(initial_state.get(name), on_missing)
+            })
+            .map(|(name, goal_atom)| (name.clone(), *goal_atom))
+            .collect();
+        let checked_goal = space.goal(&checked_goal);
+        let mismatch_count =
+            |node: &PlanNode<K>| node.mismatch_count_with(&search_goal, on_missing);
+        let marks = self.count_marks();
+        let action_indices = if space.immutable_goal_atom(&checked_goal).is_some() {
+            None
+        } else if self.cache_enabled {
+            // The cache is taken for the time of the search, so that other threads don't wait for
+            // it, and search without one instead.
+            let cache = self.applicability.lock().unwrap().take();
+            let mut cache = match cache {
+                Some(cache) if cache.initial_state == *initial_state => cache,
+                _ => {
+                    ApplicabilityCache {
+                        initial_state: initial_state.clone(),
+                        applicable: HashMap::new(),
+                    }
+                }
+            };
+            let result = space.search_with(|node| {
+                                               let positions = self.applicable(&mut cache, node);
+                                               let transitions = node.transitions_from(&positions);
+                                               self.penalized(&marks, node, transitions)
+                                                   .into_iter()
+                                                   .map(|(_, child, cost)| (child, cost))
+                                                   .collect()
+                                           },
+                                           &mismatch_count,
+                                           |node| mismatch_count(node) == 0)
+                .map(|(path, _)| self.path_indices(&marks, &path));
+            *self.applicability.lock().unwrap() = Some(cache);
+            result
+        } else {
+            space.search_with(|node| {
+                                  self.penalized(&marks, node, node.transitions())
+                                      .into_iter()
+                                      .map(|(_, child, cost)| (child, cost))
+                                      .collect()
+                              },
+                              &mismatch_count,
+                              |node| mismatch_count(node) == 0)
+                .map(|(path, _)| self.path_indices(&marks, &path))
+        };
+
+        let planned = self.actions_at(action_indices.as_ref());
+        *self.last_plan.lock().unwrap() = Some(CachedPlan {
+            initial_state: initial_state.clone(),
+            goal,
+            action_indices,
+        });
+        planned
+    }
+
+    /// Returns whether each action is planned with: if it's enabled, and when equivalent actions
+    /// are deduped, if no enabled action equivalent to it comes before it by name.
+    fn planned_actions(&self) -> Vec<bool> {
+        if !self.dedupe_equivalent {
+            return self.enabled.clone();
+        }
+
+        let is_shadowed = |index: usize| {
+            let action = &self.actions[index];
+            self.actions.iter().enumerate().any(|(other_index, other)| {
+                self.enabled[other_index] && (&other.name, other_index) < (&action.name, index) &&
+                is_equivalent(other, action)
+            })
+        };
+        (0..self.actions.len()).map(|index| self.enabled[index] && !is_shadowed(index)).collect()
+    }
+
+    /// Returns the positions of the actions applicable to the state of a node, from the cache if
+    /// they're there.
+    fn applicable(&self, cache: &mut ApplicabilityCache<K>, node: &PlanNode<K>) -> Vec<usize> {
+        if let Some(positions) = cache.applicable.get(node.changes()) {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+            return positions.clone();
+        }
+
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+        let positions = node.applicable_positions();
+        cache.applicable.insert(node.changes().clone(), positions.clone());
+        positions
+    }
+
+    /// Returns the mark counting the uses of the actions with each name, when there's a repeat
+    /// penalty.
+    fn count_marks(&self) -> BTreeMap<&str, AtomId> {
+        let mut marks = BTreeMap::new();
+        if self.repeat_penalty.is_some() {
+            for action in &self.actions {
+                let next_mark = FIRST_COUNT_MARK + marks.len() as AtomId;
+                marks.entry(action.name.as_str()).or_insert(next_mark);
+            }
+        }
+
+        marks
+    }
+
+    /// Counts the uses of the actions of transitions from a node, and makes them more expensive
+    /// the more they were used before, when there's a repeat penalty.
+    fn penalized<'s, 'a>(&self,
+                         marks: &BTreeMap<&str, AtomId>,
+                         node: &PlanNode<'s, 'a, K>,
+                         transitions: Vec<(usize, PlanNode<'s, 'a, K>, usize)>)
+                         -> Vec<(usize, PlanNode<'s, 'a, K>, usize)> {
+        let penalty = match self.repeat_penalty {
+            Some(penalty) => penalty,
+            None => return transitions,
+        };
+
+        transitions.into_iter()
+            .map(|(index, child, cost)| {
+                let mark = marks[self.actions[index].name.as_str()];
+                let uses = node.use_count(mark);
+                let child = if uses < MAX_PENALIZED_USES {
+                    child.counted(mark)
+                } else {
+                    child
+                };
+                (index, child, cost.saturating_mul(penalty.multiplier.saturating_pow(uses as u32)))
+            })
+            .collect()
+    }
+
+    /// Returns the indices of the actions applied along a path of nodes, like
+    /// `path_action_indices` does, with the uses of the actions counted as `penalized` does.
+    fn path_indices(&self, marks: &BTreeMap<&str, AtomId>, path: &[PlanNode<K>]) -> Vec<usize> {
+        if self.repeat_penalty.is_none() {
+            return path_action_indices(path);
+        }
+
+        path.windows(2)
+            .map(|pair| {
+                self.penalized(marks, &pair[0], pair[0].transitions())
+                    .into_iter()
+                    .filter(|transition| transition.1 == pair[1])
+                    .min_by_key(|transition| transition.2)
+                    .unwrap()
+                    .0
+            })
+            .collect()
+    }
+
+    /// Returns the actions of this planner at the given indices, if any.
+    fn actions_at(&self, indices: Option<&Vec<usize>>) -> Option<Vec<&Action<K>>> {
+        indices.map(|indices| indices.iter().map(|&index| &self.actions[index]).collect())
+    }
+}
+
+/// Returns `true` if two actions with different names always lead to the same states for the same
+/// cost, so that planning with either of them makes the same plans.
+fn is_equivalent<K: Atom>(a: &Action<K>, b: &Action<K>) -> bool {
+    let is_plain = |action: &Action<K>| {
+        !action.once && action.group.is_none() && action.context_check.is_none() &&
+        action.cost_fn.is_none()
+    };
+    a.name != b.name && is_plain(a) && is_plain(b) && a.cost == b.cost &&
+    a.preference == b.preference && a.pre_conditions == b.pre_conditions &&
+    a.relational_pre_conditions == b.relational_pre_conditions && a.any_of == b.any_of &&
+    a.post_conditions == b.post_conditions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Effect, plan};
+
+    #[test]
+    fn test_planner_cache() {
+        let actions = vec![Action::new("get_key", 1).produces("has_key", true),
+                           Action::new("open_door", 1)
+                               .requires("has_key", true)
+                               .produces("door_open", true)];
+        let planner = Planner::new(actions);
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_key".to_string(), false.into());
+        initial_state.insert("door_open".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("door_open".to_string(), true.into());
+
+        let expected = plan(&initial_state, &goal_state, planner.actions());
+        assert_eq!(planner.plan(&initial_state, &goal_state), expected);
+
+        // Planning again with the same inputs reuses the plan, which is noticeable once the cached
+        // plan is tampered with.
+        planner.last_plan.lock().unwrap().as_mut().unwrap().action_indices = Some(vec![]);
+        assert_eq!(planner.plan(&initial_state, &goal_state), Some(vec![]));
+
+        // Any change to the inputs makes for a new plan.
+        initial_state.insert("has_key".to_string(), true.into());
+        let expected = plan(&initial_state, &goal_state, planner.actions());
+        assert_eq!(planner.plan(&initial_state, &goal_state), expected);
+        assert_eq!(expected.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_applicability_cache() {
+        let actions = vec![Action::new("get_key", 1).produces("has_key", true),
+                           Action::new("open_door", 1)
+                               .requires("has_key", true)
+                               .produces("door_open", true),
+                           Action::new("enter", 1)
+                               .requires("door_open", true)
+                               .produces("inside", true)];
+        let planner = Planner::new(actions).with_applicability_cache();
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_key".to_string(), false.into());
+        initial_state.insert("door_open".to_string(), false.into());
+        initial_state.insert("inside".to_string(), false.into());
+
+        let mut door_open = State::new();
+        door_open.insert("door_open".to_string(), true.into());
+        let mut inside = State::new();
+        inside.insert("inside".to_string(), true.into());
+
+        let expected = plan(&initial_state, &door_open, planner.actions());
+        assert_eq!(planner.plan(&initial_state, &door_open), expected);
+        assert_eq!(planner.cache_stats(), CacheStats { hits: 0, misses: 2 });
+
+        // Getting inside goes through the states expanded on the way to the door.
+        let expected = plan(&initial_state, &inside, planner.actions());
+        assert_eq!(planner.plan(&initial_state, &inside), expected);
+        assert_eq!(planner.cache_stats(), CacheStats { hits: 2, misses: 3 });
+
+        // The cache only holds for a single initial state.
+        initial_state.insert("has_key".to_string(), true.into());
+        let expected = plan(&initial_state, &inside, planner.actions());
+        assert_eq!(planner.plan(&initial_state, &inside), expected);
+        assert_eq!(planner.cache_stats(), CacheStats { hits: 2, misses: 5 });
+    }
+
+    #[test]
+    fn test_on_missing() {
+        let actions = vec![Action::new("light_torch", 1).produces("torch_lit", true),
+                           Action::new("blow_torch_out", 1)
+                               .requires("torch_lit", true)
+                               .produces("torch_lit", false),
+                           Action::new("sleep", 1)
+                               .requires("torch_lit", false)
+                               .produces("rested", true)];
+
+        // The torch was never lit, so it isn't part of the state.
+        let mut initial_state = State::new();
+        initial_state.insert("rested".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("torch_lit".to_string(), false.into());
+
+        let planner = Planner::new(actions.clone());
+        assert_eq!(planner.plan(&initial_state, &goal_state).unwrap().len(), 2);
+        let planner = Planner::new(actions.clone()).with_on_missing(OnMissing::TreatAsFalse);
+        assert_eq!(planner.plan(&initial_state, &goal_state), Some(vec![]));
+
+        // Only goal atoms are concerned, so sleeping still takes a torch blown out.
+        goal_state.insert("rested".to_string(), true.into());
+        let names: Vec<&str> = planner.plan(&initial_state, &goal_state)
+            .unwrap()
+            .iter()
+            .map(|action| action.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["light_torch", "blow_torch_out", "sleep"]);
+
+        // Goal atoms which can't be reached at all still can't be with either handling.
+        let planner = Planner::new(vec![]).with_on_missing(OnMissing::TreatAsFalse);
+        let mut goal_state = State::new();
+        goal_state.insert("torch_lit".to_string(), false.into());
+        assert_eq!(planner.plan(&initial_state, &goal_state), Some(vec![]));
+        goal_state.insert("torch_lit".to_string(), true.into());
+        assert_eq!(planner.plan(&initial_state, &goal_state), None);
+        let planner = Planner::new(vec![]);
+        goal_state.insert("torch_lit".to_string(), false.into());
+        assert_eq!(planner.plan(&initial_state, &goal_state), None);
+    }
+
+    #[test]
+    fn test_planner_actions() {
+        let mut planner = Planner::new(vec![Action::new("smash_door", 5)
+                                                .produces("door_open", true)]);
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_key".to_string(), false.into());
+        initial_state.insert("door_open".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("door_open".to_string(), true.into());
+
+        let names = |planner: &Planner| -> Option<Vec<String>> {
+            planner.plan(&initial_state, &goal_state)
+                .map(|actions| actions.iter().map(|action| action.name.clone()).collect())
+        };
+        assert_eq!(names(&planner), Some(vec!["smash_door".to_string()]));
+
+        // Added actions are planned right away, even though there's a last plan.
+        planner.add_action(Action::new("get_key", 1).produces("has_key", true));
+        planner.add_action(Action::new("open_door", 1)
+            .requires("has_key", true)
+            .produces("door_open", true));
+        assert_eq!(names(&planner),
+                   Some(vec!["get_key".to_string(), "open_door".to_string()]));
+
+        // Disabled actions are left out of plans until they're enabled again.
+        assert!(planner.disable("get_key"));
+        assert!(!planner.is_enabled("get_key"));
+        assert_eq!(names(&planner), Some(vec!["smash_door".to_string()]));
+        assert!(planner.enable("get_key"));
+        assert!(planner.is_enabled("get_key"));
+        assert_eq!(names(&planner).unwrap().len(), 2);
+        assert!(!planner.disable("pick_lock"));
+
+        let removed = planner.remove_action("smash_door").unwrap();
+        assert_eq!(removed.name, "smash_door");
+        assert_eq!(planner.actions().len(), 2);
+        assert!(planner.disable("get_key"));
+        assert_eq!(names(&planner), None);
+        assert_eq!(planner.remove_action("smash_door"), None);
+    }
+
+    #[test]
+    fn test_repeat_penalty() {
+        let actions = vec![Action::new("chop_wood", 1).produces("wood", Effect::Add(1)),
+                           Action::new("buy_wood", 3).produces("wood", Effect::Add(2))];
+        let mut initial_state = State::new();
+        initial_state.insert("wood".to_string(), 0.into());
+        let mut goal_state = State::new();
+        goal_state.insert("wood".to_string(), 4.into());
+
+        let names = |planner: &Planner| -> Vec<String> {
+            let mut names: Vec<String> = planner.plan(&initial_state, &goal_state)
+                .unwrap()
+                .iter()
+                .map(|action| action.name.clone())
+                .collect();
+            names.sort();
+            names
+        };
+
+        // Chopping four times costs 4, but 1 + 2 + 4 + 8 = 15 with the penalty, while chopping
+        // twice and buying once costs 1 + 2 + 3 = 6.
+        let planner = Planner::new(actions.clone());
+        assert_eq!(names(&planner), vec!["chop_wood"; 4]);
+        let planner = Planner::new(actions).with_repeat_penalty(RepeatPenalty { multiplier: 2 });
+        assert_eq!(names(&planner), vec!["buy_wood", "chop_wood", "chop_wood"]);
+
+        // Counting uses doesn't keep the search from ending when there's no plan.
+        let planner = Planner::new(vec![Action::new("switch_on", 1).produces("lights_on", true),
+                                        Action::new("switch_off", 1).produces("lights_on", false),
+                                        Action::new("open_door", 1)
+                                            .requires("has_key", true)
+                                            .produces("door_open", true)])
+            .with_repeat_penalty(RepeatPenalty { multiplier: 2 });
+        let mut goal_state = State::new();
+        goal_state.insert("door_open".to_string(), true.into());
+        assert_eq!(planner.plan(&State::new(), &goal_state), None);
+    }
+
+    #[test]
+    fn test_equivalents_deduped() {
+        let actions = vec![Action::new("walk_to_door", 1).produces("at_door", true),
+                           Action::new("stroll_to_door", 1).produces("at_door", true),
+                           Action::new("run_to_door", 1).produces("at_door", true).once(),
+                           Action::new("open_door", 1)
+                               .requires("at_door", true)
+                               .produces("door_open", true)];
+        let mut goal_state = State::new();
+        goal_state.insert("door_open".to_string(), true.into());
+
+        // The first of the equivalent actions by name is planned, as without deduping.
+        let names = |planner: &Planner| -> Vec<String> {
+            planner.plan(&State::new(), &goal_state)
+                .unwrap()
+                .iter()
+                .map(|action| action.name.clone())
+                .collect()
+        };
+        let mut planner = Planner::new(actions.clone()).with_equivalents_deduped();
+        assert_eq!(names(&planner), names(&Planner::new(actions.clone())));
+        assert_eq!(names(&planner), vec!["run_to_door", "open_door"]);
+
+        // When only equivalent actions compete, deduping them doesn't change the plan either,
+        // whatever the order of the actions.
+        let mut plain_actions: Vec<Action> =
+            actions.iter().filter(|action| !action.once).cloned().collect();
+        for _ in 0..2 {
+            let deduped = Planner::new(plain_actions.clone()).with_equivalents_deduped();
+            assert_eq!(names(&deduped), names(&Planner::new(plain_actions.clone())));
+            assert_eq!(names(&deduped), vec!["stroll_to_door", "open_door"]);
+            plain_actions.reverse();
+        }
+
+        // Actions applicable only once aren't equivalent to any other.
+        let equivalents: Vec<&str> = planner.equivalent_actions("walk_to_door")
+            .iter()
+            .map(|action| action.name.as_str())
+            .collect();
+        assert_eq!(equivalents, vec!["walk_to_door", "stroll_to_door"]);
+
+        // Once the first one is disabled, the next one is planned instead.
+        planner.disable("run_to_door");
+        assert_eq!(names(&planner), vec!["stroll_to_door", "open_door"]);
+        planner.disable("stroll_to_door");
+        assert_eq!(names(&planner), vec!["walk_to_door", "open_door"]);
+        assert_eq!(planner.equivalent_actions("walk_to_door").len(), 1);
+        assert!(planner.equivalent_actions("fly_to_door").is_empty());
+    }
+}
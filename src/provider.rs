@@ -0,0 +1,170 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Planning with actions generated on demand for each state, instead of a fixed set of actions.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+#[cfg(not(feature = "std"))]
+use prelude::*;
+use execute::{apply, unmet_pre_condition};
+use {Action, Atom, State, ToGoal};
+
+/// Something which generates the actions to plan with for each state, such as when actions are
+/// parametric, like moving to any tile of a grid, so that they can't all be listed up front.
+pub trait ActionProvider<K: Atom = String> {
+    /// Returns the actions which may be applied to a state. The planner leaves out the actions
+    /// whose pre-conditions or context check the state doesn't fulfill, so those can be returned
+    /// too.
+    fn applicable_actions(&self, state: &State<K>) -> Vec<Action<K>>;
+}
+
+impl<K: Atom, F: Fn(&State<K>) -> Vec<Action<K>>> ActionProvider<K> for F {
+    fn applicable_actions(&self, state: &State<K>) -> Vec<Action<K>> {
+        self(state)
+    }
+}
+
+/// A state reached by the search, along with the way it was reached.
+struct Record<K: Atom> {
+    state: State<K>,
+    parent: Option<(usize, Action<K>)>,
+}
+
+/// Formulates a plan like `plan` does, with the actions `provider` generates for each state the
+/// search goes through, and returns copies of the planned actions.
+///
+/// As the actions are generated anew for each state, actions applicable only once and groups of
+/// actions don't limit the plan.
+pub fn plan_with_provider<G, K, P>(initial_state: &State<K>,
+                                   goal_state: &G,
+                                   provider: &P)
+                                   -> Option<Vec<Action<K>>>
+    where G: ToGoal<K> + ?Sized,
+          K: Atom,
+          P: ActionProvider<K> + ?Sized
+{
+    let goal = goal_state.to_goal();
+    let mismatch_count = |state: &State<K>| {
+        goal.iter().filter(|&(name, goal_atom)| !goal_atom.is_met_by(state.get(name))).count()
+    };
+
+    let mut records = vec![Record {
+                               state: initial_state.clone(),
+                               parent: None,
+                           }];
+    let mut best_g_costs = BTreeMap::new();
+    best_g_costs.insert(initial_state.clone(), 0);
+    let mut open = BinaryHeap::new();
+    let h_cost = mismatch_count(initial_state);
+    open.push(Reverse((h_cost, h_cost, 0)));
+
+    while let Some(Reverse((f_cost, h_cost, index))) = open.pop() {
+        // Skips the records of states reached in a cheaper way since.
+        let g_cost = f_cost - h_cost;
+        if best_g_costs[&records[index].state] < g_cost {
+            continue;
+        }
+        if h_cost == 0 {
+            let mut actions = vec![];
+            let mut index = index;
+            while let Some((parent, ref action)) = records[index].parent {
+                actions.push(action.clone());
+                index = parent;
+            }
+            actions.reverse();
+            return Some(actions);
+        }
+
+        for action in provider.applicable_actions(&records[index].state) {
+            let state = &records[index].state;
+            if unmet_pre_condition(&action, state).is_some() ||
+               action.context_check.as_ref().is_some_and(|check| !check(&())) {
+                continue;
+            }
+            let cost = match action.cost_fn {
+                Some(ref cost_fn) => cost_fn(state, &()),
+                None => action.cost,
+            };
+
+            let mut child = state.clone();
+            apply(&action, &mut child);
+            let child_g_cost = g_cost + cost;
+            if best_g_costs.get(&child).is_some_and(|&best_g_cost| best_g_cost <= child_g_cost) {
+                continue;
+            }
+
+            // Nodes with the least estimated total cost are expanded first, and the closest to the
+            // goal among those, as `plan` does.
+            let child_h_cost = mismatch_count(&child);
+            best_g_costs.insert(child.clone(), child_g_cost);
+            open.push(Reverse((child_g_cost + child_h_cost, child_h_cost, records.len())));
+            records.push(Record {
+                state: child,
+                parent: Some((index, action)),
+            });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Effect, Value, plan};
+
+    #[test]
+    fn test_plan_with_provider() {
+        // Moving to any neighboring tile of a 5x5 grid, except through the wall at x = 2.
+        let provider = |state: &State| -> Vec<Action> {
+            let (x, y) = match (state["x"], state["y"]) {
+                (Value::Int(x), Value::Int(y)) => (x, y),
+                _ => unreachable!(),
+            };
+            let mut actions = vec![];
+            for &(dx, dy) in &[(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                let (to_x, to_y) = (x + dx, y + dy);
+                let is_on_grid = (0..5).contains(&to_x) && (0..5).contains(&to_y);
+                if !is_on_grid || (to_x == 2 && to_y < 4) {
+                    continue;
+                }
+                actions.push(Action::new(format!("move_to_{}_{}", to_x, to_y), 1)
+                    .produces("x", Effect::Set(to_x.into()))
+                    .produces("y", Effect::Set(to_y.into())));
+            }
+            actions
+        };
+
+        let mut initial_state = State::new();
+        initial_state.insert("x".to_string(), 0.into());
+        initial_state.insert("y".to_string(), 0.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("x".to_string(), 4.into());
+        goal_state.insert("y".to_string(), 0.into());
+
+        // Going around the wall takes 4 moves up, 4 moves right, and 4 moves down.
+        let planned = plan_with_provider(&initial_state, &goal_state, &provider).unwrap();
+        assert_eq!(planned.len(), 12);
+        assert_eq!(planned[11].name, "move_to_4_0");
+
+        // A fixed set of actions gives the same plan as `plan` does.
+        let actions = [Action::new("get_key", 1).produces("has_key", true),
+                       Action::new("open_door", 1)
+                           .requires("has_key", true)
+                           .produces("door_open", true)];
+        let mut initial_state = State::new();
+        initial_state.insert("has_key".to_string(), false.into());
+        let mut goal_state = State::new();
+        goal_state.insert("door_open".to_string(), true.into());
+        let provider = |_: &State| actions.to_vec();
+        let planned = plan_with_provider(&initial_state, &goal_state, &provider);
+        let expected: Vec<Action> =
+            plan(&initial_state, &goal_state, &actions).unwrap().into_iter().cloned().collect();
+        assert_eq!(planned, Some(expected));
+
+        // Nothing to generate, nowhere to go.
+        let planned = plan_with_provider(&initial_state, &goal_state, &|_: &State| vec![]);
+        assert_eq!(planned, None);
+    }
+}
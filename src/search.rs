@@ -0,0 +1,1448 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! The search over the graph of world states.
+//!
+//! Atom names are interned once per search into small integer identifiers, so that the nodes of
+//! the graph, which get hashed and compared over and over, only ever deal with integers.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap};
+use std::hash::{Hash, Hasher};
+#[cfg(feature = "std")]
+use std::ops::Add;
+#[cfg(feature = "std")]
+use pathfinding::num_traits::Zero;
+#[cfg(feature = "std")]
+use pathfinding::prelude::{astar, astar_bag_collect, idastar};
+#[cfg(not(feature = "std"))]
+use prelude::*;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use {Action, Atom, Condition, Effect, GoalAtom, OnMissing, RelationalPre, State, ToGoal, Value};
+#[cfg(feature = "std")]
+use TieBias;
+
+/// The identifier of an atom within a search.
+pub type AtomId = u32;
+
+/// A goal, where atoms are referred to by their identifiers.
+pub type SearchGoal = Vec<(AtomId, GoalAtom)>;
+
+/// The atoms of a state whose values differ from the initial state of a search, with their values.
+///
+/// Changes also mark the actions applicable only once and the groups of actions which were applied
+/// to get to the state, as if they were atoms, so that the same state reached with different
+/// actions left to apply isn't mistaken for the same node.
+///
+/// With the `im` feature, changes are persistent maps, which the changes of a child node share most
+/// of their structure with, instead of being copied from those of its parent. As each node of such
+/// a map has room for dozens of entries, this only saves memory when states differ from the
+/// initial state by many atoms, see the `memory` benchmark.
+#[cfg(not(feature = "im"))]
+pub type Changes = BTreeMap<AtomId, Value>;
+#[cfg(feature = "im")]
+pub type Changes = im::OrdMap<AtomId, Value>;
+
+/// The identifier of the first mark of an action applicable only once, in changes. Marks come
+/// after all atoms, which never get as many, and the marks of groups come after those of actions.
+const FIRST_USED_MARK: AtomId = 1 << 31;
+
+/// The identifier of the first count of uses of actions, in changes, which comes after the marks of
+/// actions applicable only once and of groups, which never get as many either.
+#[cfg(feature = "std")]
+pub const FIRST_COUNT_MARK: AtomId = 3 << 30;
+
+/// The goal atoms left to fulfill by the initial state in a backward search, where atoms are
+/// referred to by their identifiers.
+#[cfg(feature = "std")]
+pub type Requirements = BTreeMap<AtomId, GoalAtom>;
+
+/// A cost which can be fractional, ordered so that it can be searched with.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug)]
+struct RealCost(f64);
+
+#[cfg(feature = "std")]
+impl PartialEq for RealCost {
+    fn eq(&self, other: &RealCost) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+#[cfg(feature = "std")]
+impl Eq for RealCost {}
+
+#[cfg(feature = "std")]
+impl PartialOrd for RealCost {
+    fn partial_cmp(&self, other: &RealCost) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Ord for RealCost {
+    fn cmp(&self, other: &RealCost) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Add for RealCost {
+    type Output = RealCost;
+
+    fn add(self, other: RealCost) -> RealCost {
+        RealCost(self.0 + other.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Zero for RealCost {
+    fn zero() -> RealCost {
+        RealCost(0.0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == 0.0
+    }
+}
+
+/// A cost along with a tie-breaking key, ordered by cost first, then by key, so that nodes with
+/// the same estimated total cost are expanded in the order of their keys.
+#[cfg(feature = "std")]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+struct BiasedCost(usize, usize);
+
+#[cfg(feature = "std")]
+impl Add for BiasedCost {
+    type Output = BiasedCost;
+
+    fn add(self, other: BiasedCost) -> BiasedCost {
+        BiasedCost(self.0 + other.0, self.1 + other.1)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Zero for BiasedCost {
+    fn zero() -> BiasedCost {
+        BiasedCost(0, 0)
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == BiasedCost(0, 0)
+    }
+}
+
+/// An entry of the open set of `SearchSpace::search_best_first`, ordered the way the A* of
+/// `pathfinding` orders its own, so that the greatest entry has the least estimated total cost,
+/// then the greatest cost of its parent node. The node and its own cost don't take part.
+#[cfg(any(test, not(feature = "std")))]
+struct OpenEntry {
+    estimated_cost: usize,
+    parent_cost: usize,
+    cost: usize,
+    index: usize,
+}
+
+#[cfg(any(test, not(feature = "std")))]
+impl PartialEq for OpenEntry {
+    fn eq(&self, other: &OpenEntry) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+#[cfg(any(test, not(feature = "std")))]
+impl Eq for OpenEntry {}
+
+#[cfg(any(test, not(feature = "std")))]
+impl PartialOrd for OpenEntry {
+    fn partial_cmp(&self, other: &OpenEntry) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(any(test, not(feature = "std")))]
+impl Ord for OpenEntry {
+    fn cmp(&self, other: &OpenEntry) -> Ordering {
+        other.estimated_cost
+            .cmp(&self.estimated_cost)
+            .then(self.parent_cost.cmp(&other.parent_cost))
+    }
+}
+
+/// A cost function of an action, bound to the context of the search.
+type BoundCostFn<'a, K> = Box<dyn Fn(&State<K>) -> usize + 'a>;
+
+/// An allowed action, where atoms are referred to by their identifiers.
+struct SearchAction {
+    index: usize,
+    cost: usize,
+    used_marks: Vec<AtomId>,
+    pre_conditions: Vec<(AtomId, Condition)>,
+    relational_pre_conditions: Vec<RelationalPre<AtomId>>,
+    any_of: Vec<Vec<(AtomId, Condition)>>,
+    post_conditions: Vec<(AtomId, Effect)>,
+}
+
+impl SearchAction {
+    /// Returns the requirements to fulfill before applying this action for the given requirements
+    /// to be fulfilled once it has been applied, unless the action doesn't help fulfilling them.
+    ///
+    /// Only equality pre-conditions and absolute effects can be regressed through, so actions
+    /// having other ones, or relational pre-conditions, never help.
+    #[cfg(feature = "std")]
+    fn regress(&self, requirements: &Requirements) -> Option<Requirements> {
+        // Whether an action was already applied isn't known while searching backward.
+        if !self.used_marks.is_empty() || !self.relational_pre_conditions.is_empty() ||
+           !self.any_of.is_empty() {
+            return None;
+        }
+
+        let mut regressed = requirements.clone();
+        let mut is_relevant = false;
+        for &(id, effect) in &self.post_conditions {
+            match (effect, requirements.get(&id)) {
+                (Effect::Set(value), Some(&GoalAtom::Required(required))) if value == required => {
+                    regressed.remove(&id);
+                    is_relevant = true;
+                }
+                (Effect::Set(_), Some(&GoalAtom::Present)) => {
+                    regressed.remove(&id);
+                    is_relevant = true;
+                }
+                (_, None) => {}
+                _ => return None,
+            }
+        }
+        if !is_relevant {
+            return None;
+        }
+
+        for &(id, condition) in &self.pre_conditions {
+            let required = match condition {
+                Condition::Equal(value) => GoalAtom::Required(value),
+                _ => return None,
+            };
+            if regressed.insert(id, required).is_some_and(|previous| previous != required) {
+                return None;
+            }
+        }
+
+        Some(regressed)
+    }
+}
+
+/// The atoms and actions a search works with.
+///
+/// The cost functions of the actions are kept apart from the rest of the actions, bound to the
+/// context, so that everything else can be shared between threads whatever the cost functions and
+/// the context are.
+///
+/// Actions are tried by name, then by cost, rather than in the order they were given in, so that
+/// plans don't depend on how the set of allowed actions was put together.
+pub struct SearchSpace<'a, K: 'a> {
+    ids: BTreeMap<K, AtomId>,
+    names: Vec<K>,
+    initial_values: Vec<Option<Value>>,
+    actions: Vec<SearchAction>,
+    cost_fns: Vec<Option<BoundCostFn<'a, K>>>,
+    order: Vec<usize>,
+}
+
+impl<'a, K: Atom> SearchSpace<'a, K> {
+    /// Makes a search space from an initial state and a set of allowed actions.
+    ///
+    /// The context is the same for the whole search, so the context checks of the actions are
+    /// only evaluated once here, and actions failing theirs are left out of the search entirely.
+    /// Cost functions are called with the context whenever an action is applied.
+    pub fn new<C>(initial_state: &State<K>,
+                  allowed_actions: &'a [Action<K, C>],
+                  context: &'a C)
+                  -> SearchSpace<'a, K> {
+        SearchSpace::filtered(initial_state, allowed_actions, context, |_| true)
+    }
+
+    /// Makes a search space like `new` does, leaving out the actions at the indices for which
+    /// `is_enabled` returns `false`.
+    pub fn filtered<C, F>(initial_state: &State<K>,
+                          allowed_actions: &'a [Action<K, C>],
+                          context: &'a C,
+                          mut is_enabled: F)
+                          -> SearchSpace<'a, K>
+        where F: FnMut(usize) -> bool
+    {
+        let mut space = SearchSpace {
+            ids: BTreeMap::new(),
+            names: vec![],
+            initial_values: vec![],
+            actions: vec![],
+            cost_fns: vec![],
+            order: vec![],
+        };
+
+        for (name, value) in initial_state {
+            let id = space.intern(name);
+            space.initial_values[id as usize] = Some(*value);
+        }
+
+        let mut group_marks = BTreeMap::new();
+        for (index, action) in allowed_actions.iter().enumerate() {
+            if !is_enabled(index) {
+                continue;
+            }
+            if let Some(ref check) = action.context_check {
+                if !check(context) {
+                    continue;
+                }
+            }
+
+            let pre_conditions = action.pre_conditions
+                .iter()
+                .map(|(name, condition)| (space.intern(name), *condition))
+                .collect();
+            let relational_pre_conditions = action.relational_pre_conditions
+                .iter()
+                .map(|pre| {
+                    RelationalPre {
+                        left: space.intern(&pre.left),
+                        right: space.intern(&pre.right),
+                        equal: pre.equal,
+                    }
+                })
+                .collect();
+            let any_of = action.any_of
+                .iter()
+                .map(|alternative| {
+                    alternative.iter()
+                        .map(|(name, value)| (space.intern(name), Condition::Equal(*value)))
+                        .collect()
+                })
+                .collect();
+            let post_conditions = action.post_conditions
+                .iter()
+                .map(|(name, effect)| (space.intern(name), *effect))
+                .collect();
+            let mut used_marks = vec![];
+            if action.once {
+                used_marks.push(FIRST_USED_MARK + space.actions.len() as AtomId);
+            }
+            if let Some(ref group) = action.group {
+                let first_group_mark = FIRST_USED_MARK + allowed_actions.len() as AtomId;
+                let next_group_mark = first_group_mark + group_marks.len() as AtomId;
+                used_marks.push(*group_marks.entry(group.as_str()).or_insert(next_group_mark));
+            }
+            space.actions.push(SearchAction {
+                index,
+                cost: action.cost,
+                used_marks,
+                pre_conditions,
+                relational_pre_conditions,
+                any_of,
+                post_conditions,
+            });
+            space.cost_fns.push(action.cost_fn.as_ref().map(|cost_fn| {
+                Box::new(move |state: &State<K>| cost_fn(state, context)) as BoundCostFn<K>
+            }));
+        }
+
+        let mut order: Vec<usize> = (0..space.actions.len()).collect();
+        order.sort_by(|&a, &b| {
+            let (a, b) = (&space.actions[a], &space.actions[b]);
+            let (a_action, b_action) = (&allowed_actions[a.index], &allowed_actions[b.index]);
+            (&a_action.name, a.cost, a.index).cmp(&(&b_action.name, b.cost, b.index))
+        });
+        space.order = order;
+
+        space
+    }
+
+    /// Returns the identifier of an atom, allocating one if it's the first time it's seen.
+    fn intern(&mut self, name: &K) -> AtomId {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+
+        let id = self.names.len() as AtomId;
+        self.ids.insert(name.clone(), id);
+        self.names.push(name.clone());
+        self.initial_values.push(None);
+        id
+    }
+
+    /// Translates a goal so that it can be used by the search.
+    pub fn goal<G>(&mut self, goal: &G) -> SearchGoal
+        where G: ToGoal<K> + ?Sized
+    {
+        goal.to_goal().iter().map(|(name, goal_atom)| (self.intern(name), *goal_atom)).collect()
+    }
+
+    /// Returns the first atom of a goal translated by `goal` which the initial state doesn't
+    /// fulfill, and which no action can change so that it does, if any. The goal can then never be
+    /// reached, as effects never remove atoms either.
+    pub fn immutable_goal_atom(&self, goal: &SearchGoal) -> Option<&K> {
+        goal.iter()
+            .find(|&&(id, ref goal_atom)| {
+                if goal_atom.is_met_by(self.initial_values[id as usize].as_ref()) {
+                    return false;
+                }
+                let is_changed = |action: &SearchAction| {
+                    action.post_conditions.iter().any(|effect| effect.0 == id)
+                };
+                match *goal_atom {
+                    GoalAtom::MustBeAbsent => true,
+                    _ => !self.actions.iter().any(is_changed),
+                }
+            })
+            .map(|&(id, _)| &self.names[id as usize])
+    }
+
+    /// Returns the weight of each atom of a goal translated by `goal`, in the same order, where
+    /// atoms with no listed weight weigh 1.
+    pub fn goal_weights(&self, goal: &SearchGoal, weights: &BTreeMap<K, usize>) -> Vec<usize> {
+        goal.iter()
+            .map(|&(id, _)| weights.get(&self.names[id as usize]).cloned().unwrap_or(1))
+            .collect()
+    }
+
+    /// Replaces the costs of the actions for which `cost` returns one, given their indices in the
+    /// set of allowed actions, including those of the actions with a cost function.
+    pub fn override_costs<F>(&mut self, mut cost: F)
+        where F: FnMut(usize) -> Option<usize>
+    {
+        for (action, cost_fn) in self.actions.iter_mut().zip(&mut self.cost_fns) {
+            if let Some(cost) = cost(action.index) {
+                action.cost = cost;
+                *cost_fn = None;
+            }
+        }
+    }
+
+    /// Makes the initial plan node, without a parent.
+    pub fn initial_node<'s>(&'s self) -> PlanNode<'s, 'a, K> {
+        self.node(Changes::new())
+    }
+
+    /// Makes the plan node of the state having the given changes from the initial state.
+    pub fn node<'s>(&'s self, changes: Changes) -> PlanNode<'s, 'a, K> {
+        PlanNode {
+            space: self,
+            changes,
+        }
+    }
+
+    /// Runs our search over the states graph, and returns the path of nodes leading to a node for
+    /// which `success` is `true`, starting with the initial node, along with its cost.
+    pub fn search<'s, FH, FS>(&'s self,
+                              heuristic: FH,
+                              success: FS)
+                              -> Option<(Vec<PlanNode<'s, 'a, K>>, usize)>
+        where FH: FnMut(&PlanNode<'s, 'a, K>) -> usize,
+              FS: FnMut(&PlanNode<'s, 'a, K>) -> bool
+    {
+        self.search_with(|node| node.possible_next_nodes(), heuristic, success)
+    }
+
+    /// Runs our search over the states graph like `search` does, with custom successors.
+    #[cfg(feature = "std")]
+    pub fn search_with<'s, FN, FH, FS>(&'s self,
+                                       successors: FN,
+                                       heuristic: FH,
+                                       mut success: FS)
+                                       -> Option<(Vec<PlanNode<'s, 'a, K>>, usize)>
+        where FN: FnMut(&PlanNode<'s, 'a, K>) -> Vec<(PlanNode<'s, 'a, K>, usize)>,
+              FH: FnMut(&PlanNode<'s, 'a, K>) -> usize,
+              FS: FnMut(&PlanNode<'s, 'a, K>) -> bool
+    {
+        // Checking the initial node first spares setting the search up for nothing.
+        let initial = self.initial_node();
+        if success(&initial) {
+            return Some((vec![initial], 0));
+        }
+
+        astar(&initial, successors, heuristic, success)
+    }
+
+    /// Runs our search over the states graph like `search` does, with custom successors.
+    #[cfg(not(feature = "std"))]
+    pub fn search_with<'s, FN, FH, FS>(&'s self,
+                                       successors: FN,
+                                       heuristic: FH,
+                                       success: FS)
+                                       -> Option<(Vec<PlanNode<'s, 'a, K>>, usize)>
+        where FN: FnMut(&PlanNode<'s, 'a, K>) -> Vec<(PlanNode<'s, 'a, K>, usize)>,
+              FH: FnMut(&PlanNode<'s, 'a, K>) -> usize,
+              FS: FnMut(&PlanNode<'s, 'a, K>) -> bool
+    {
+        self.search_best_first(successors, heuristic, success)
+    }
+
+    /// Runs our search over the states graph like `search_with` does, telling `successors` the cost
+    /// of the cheapest way to the node it expands, along with the costs of the cheapest ways found
+    /// yet to the nodes reached, which the nodes it returns get recorded in afterwards.
+    pub fn search_with_costs<'s, FN, FH, FS>(&'s self,
+                                             mut successors: FN,
+                                             heuristic: FH,
+                                             success: FS)
+                                             -> Option<(Vec<PlanNode<'s, 'a, K>>, usize)>
+        where FN: FnMut(&PlanNode<'s, 'a, K>, usize, &mut ReachedCosts)
+                        -> Vec<(PlanNode<'s, 'a, K>, usize)>,
+              FH: FnMut(&PlanNode<'s, 'a, K>) -> usize,
+              FS: FnMut(&PlanNode<'s, 'a, K>) -> bool
+    {
+        // The search doesn't tell the cost of the way to the node it expands, so the cheapest way
+        // to each node reached is tracked along with it, as it's the one nodes are expanded from.
+        let mut costs = ReachedCosts(BTreeMap::new());
+        costs.reach(&self.initial_node(), 0);
+        self.search_with(|node| {
+                             let g_cost = costs.0[node.changes()];
+                             let children = successors(node, g_cost, &mut costs);
+                             for (child, cost) in &children {
+                                 costs.reach(child, g_cost + cost);
+                             }
+                             children
+                         },
+                         heuristic,
+                         success)
+    }
+
+    /// Runs our search over the states graph like `search_with` does, with a best-first loop of
+    /// its own instead of the A* of `pathfinding`, which needs the standard library.
+    ///
+    /// Nodes are expanded in the same order as A* expands them, so that both find the same plans.
+    #[cfg(any(test, not(feature = "std")))]
+    fn search_best_first<'s, FN, FH, FS>(&'s self,
+                                         mut successors: FN,
+                                         mut heuristic: FH,
+                                         mut success: FS)
+                                         -> Option<(Vec<PlanNode<'s, 'a, K>>, usize)>
+        where FN: FnMut(&PlanNode<'s, 'a, K>) -> Vec<(PlanNode<'s, 'a, K>, usize)>,
+              FH: FnMut(&PlanNode<'s, 'a, K>) -> usize,
+              FS: FnMut(&PlanNode<'s, 'a, K>) -> bool
+    {
+        let initial = self.initial_node();
+        if success(&initial) {
+            return Some((vec![initial], 0));
+        }
+
+        let mut open = BinaryHeap::new();
+        open.push(OpenEntry {
+            estimated_cost: heuristic(&initial),
+            parent_cost: 0,
+            cost: 0,
+            index: 0,
+        });
+        // Each node reached is recorded along with the index of the record of its parent and the
+        // cost of the cheapest way to it found yet, which both change when a cheaper way is found.
+        let mut indices = BTreeMap::new();
+        indices.insert(initial.changes().clone(), 0);
+        let mut records = vec![(initial, None, 0)];
+
+        while let Some(OpenEntry { cost, index, .. }) = open.pop() {
+            if success(&records[index].0) {
+                let mut path = vec![];
+                let mut index = Some(index);
+                while let Some(current) = index {
+                    path.push(records[current].0.clone());
+                    index = records[current].1;
+                }
+                path.reverse();
+                return Some((path, cost));
+            }
+            // Skips the entries of nodes reached in a cheaper way since.
+            if cost > records[index].2 {
+                continue;
+            }
+
+            for (child, move_cost) in successors(&records[index].0) {
+                let child_cost = cost + move_cost;
+                let child_index = match indices.get(child.changes()) {
+                    Some(&child_index) if records[child_index].2 <= child_cost => continue,
+                    Some(&child_index) => {
+                        records[child_index].1 = Some(index);
+                        records[child_index].2 = child_cost;
+                        child_index
+                    }
+                    None => {
+                        indices.insert(child.changes().clone(), records.len());
+                        records.push((child, Some(index), child_cost));
+                        records.len() - 1
+                    }
+                };
+                open.push(OpenEntry {
+                    estimated_cost: child_cost + heuristic(&records[child_index].0),
+                    parent_cost: cost,
+                    cost: child_cost,
+                    index: child_index,
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Runs our search over the states graph like `search_with` does, breaking ties between nodes
+    /// with the same estimated total cost as told by `bias`.
+    ///
+    /// Only the heuristic gives a tie-breaking key, the costs of the actions giving none, so that
+    /// the key of a node only depends on its estimated cost left.
+    #[cfg(feature = "std")]
+    pub fn search_biased<'s, FN, FH, FS>(&'s self,
+                                         bias: TieBias,
+                                         mut successors: FN,
+                                         mut heuristic: FH,
+                                         mut success: FS)
+                                         -> Option<(Vec<PlanNode<'s, 'a, K>>, usize)>
+        where FN: FnMut(&PlanNode<'s, 'a, K>) -> Vec<(PlanNode<'s, 'a, K>, usize)>,
+              FH: FnMut(&PlanNode<'s, 'a, K>) -> usize,
+              FS: FnMut(&PlanNode<'s, 'a, K>) -> bool
+    {
+        let initial = self.initial_node();
+        if success(&initial) {
+            return Some((vec![initial], 0));
+        }
+
+        let (path, cost) = astar(&initial,
+                                 |node| {
+                                     successors(node)
+                                         .into_iter()
+                                         .map(|(child, cost)| (child, BiasedCost(cost, 0)))
+                                         .collect::<Vec<_>>()
+                                 },
+                                 |node| {
+                                     let h_cost = heuristic(node);
+                                     match bias {
+                                         TieBias::PreferGoal => BiasedCost(h_cost, h_cost),
+                                         TieBias::PreferStart => {
+                                             BiasedCost(h_cost, usize::MAX - h_cost)
+                                         }
+                                     }
+                                 },
+                                 success)?;
+        Some((path, cost.0))
+    }
+
+    /// Runs our search over the states graph like `search` does, but returns all the paths that
+    /// share the optimal cost.
+    #[cfg(feature = "std")]
+    pub fn search_all<'s, FH, FS>(&'s self,
+                                  heuristic: FH,
+                                  mut success: FS)
+                                  -> Option<(Vec<Vec<PlanNode<'s, 'a, K>>>, usize)>
+        where FH: FnMut(&PlanNode<'s, 'a, K>) -> usize,
+              FS: FnMut(&PlanNode<'s, 'a, K>) -> bool
+    {
+        let initial = self.initial_node();
+        if success(&initial) {
+            return Some((vec![vec![initial]], 0));
+        }
+
+        astar_bag_collect(&initial,
+                          |node| node.possible_next_nodes(),
+                          heuristic,
+                          success)
+    }
+
+    /// Runs our search over the states graph like `search` does, but only over the paths made of
+    /// at most `max_len` actions.
+    ///
+    /// The same state reached in different numbers of actions is searched from separately, since
+    /// a way there that's more expensive may leave more actions to get to the goal from there.
+    #[cfg(feature = "std")]
+    pub fn search_bounded<'s, FH, FS>(&'s self,
+                                      max_len: usize,
+                                      mut heuristic: FH,
+                                      mut success: FS)
+                                      -> Option<(Vec<PlanNode<'s, 'a, K>>, usize)>
+        where FH: FnMut(&PlanNode<'s, 'a, K>) -> usize,
+              FS: FnMut(&PlanNode<'s, 'a, K>) -> bool
+    {
+        let initial = self.initial_node();
+        if success(&initial) {
+            return Some((vec![initial], 0));
+        }
+
+        astar(&(initial, 0),
+              |&(ref node, depth)| if depth < max_len {
+                  node.possible_next_nodes()
+                      .into_iter()
+                      .map(|(child, cost)| ((child, depth + 1), cost))
+                      .collect()
+              } else {
+                  vec![]
+              },
+              |step| heuristic(&step.0),
+              |step| success(&step.0))
+            .map(|(path, cost)| (path.into_iter().map(|(node, _)| node).collect(), cost))
+    }
+
+    /// Runs our search over the states graph like `search` does, using iterative-deepening A*,
+    /// which only keeps the path being explored in memory.
+    #[cfg(feature = "std")]
+    pub fn search_ida<'s, FH, FS>(&'s self,
+                                  heuristic: FH,
+                                  mut success: FS)
+                                  -> Option<(Vec<PlanNode<'s, 'a, K>>, usize)>
+        where FH: FnMut(&PlanNode<'s, 'a, K>) -> usize,
+              FS: FnMut(&PlanNode<'s, 'a, K>) -> bool
+    {
+        let initial = self.initial_node();
+        if success(&initial) {
+            return Some((vec![initial], 0));
+        }
+
+        idastar(&initial, |node| node.possible_next_nodes(), heuristic, success)
+    }
+
+    /// Runs our search over the states graph like `search` does, with fractional costs, and returns
+    /// the indices of the actions of the cheapest plan found among the actions the search was made
+    /// from, in order, along with its cost.
+    ///
+    /// The cost of an action is given by `cost` from its index and the state it's applied to.
+    #[cfg(feature = "std")]
+    pub fn search_real<'s, FC, FH, FS>(&'s self,
+                                       mut cost: FC,
+                                       mut heuristic: FH,
+                                       mut success: FS)
+                                       -> Option<(Vec<usize>, f64)>
+        where FC: FnMut(usize, &State<K>) -> f64,
+              FH: FnMut(&PlanNode<'s, 'a, K>) -> f64,
+              FS: FnMut(&PlanNode<'s, 'a, K>) -> bool
+    {
+        let initial = self.initial_node();
+        if success(&initial) {
+            return Some((vec![], 0.0));
+        }
+
+        let mut transitions = |node: &PlanNode<'s, 'a, K>| {
+            let state = node.current_state();
+            node.transitions()
+                .into_iter()
+                .map(|(index, child, _)| (index, child, RealCost(cost(index, &state))))
+                .collect::<Vec<_>>()
+        };
+        let (path, total_cost) = astar(&initial,
+                                       |node| {
+                                           transitions(node)
+                                               .into_iter()
+                                               .map(|(_, child, cost)| (child, cost))
+                                               .collect::<Vec<_>>()
+                                       },
+                                       |node| RealCost(heuristic(node)),
+                                       success)?;
+
+        // As with `path_actions`, the cheapest action between each pair of nodes is looked up.
+        let indices = path.windows(2)
+            .map(|pair| {
+                let cheapest = transitions(&pair[0])
+                    .into_iter()
+                    .filter(|transition| transition.1 == pair[1])
+                    .min_by_key(|transition| transition.2);
+                cheapest.map(|(index, _, _)| index).unwrap()
+            })
+            .collect();
+
+        Some((indices, total_cost.0))
+    }
+
+    /// Searches backward from a goal, by regressing its atoms through the actions fulfilling them
+    /// until the initial state fulfills the requirements left, and returns the indices of the
+    /// actions of the cheapest plan found among the actions the search was made from, in order,
+    /// along with its cost.
+    ///
+    /// Cost functions aren't called, since the state an action is applied to isn't known while
+    /// searching backward, so the static cost of the actions is used instead.
+    #[cfg(feature = "std")]
+    pub fn search_backward(&self, goal: &SearchGoal) -> Option<(Vec<usize>, usize)> {
+        let start: Requirements = goal.iter().cloned().collect();
+        let (path, cost) = astar(&start,
+                                 |requirements| {
+                                     self.actions
+                                         .iter()
+                                         .filter_map(|action| {
+                                             action.regress(requirements)
+                                                 .map(|regressed| (regressed, action.cost))
+                                         })
+                                         .collect::<Vec<_>>()
+                                 },
+                                 |requirements| self.unmet_count(requirements),
+                                 |requirements| self.unmet_count(requirements) == 0)?;
+
+        // The path goes backward, from the goal to the requirements the initial state fulfills.
+        let mut indices: Vec<usize> = path.windows(2)
+            .map(|pair| {
+                let cheapest = self.actions
+                    .iter()
+                    .filter(|action| action.regress(&pair[0]).as_ref() == Some(&pair[1]))
+                    .min_by_key(|action| action.cost);
+                cheapest.map(|action| action.index).unwrap()
+            })
+            .collect();
+        indices.reverse();
+
+        Some((indices, cost))
+    }
+
+    /// Goes through the nodes of the states graph by increasing cost of the cheapest way to them
+    /// from the initial node, starting with it, leaving out those costing more than `max_cost`.
+    pub fn by_cost<'s>(&'s self, max_cost: usize) -> CostOrder<'s, 'a, K> {
+        let mut best_g_costs = BTreeMap::new();
+        best_g_costs.insert(self.initial_node().changes().clone(), 0);
+        let mut open = BinaryHeap::new();
+        open.push(Reverse((0, 0)));
+        CostOrder {
+            nodes: vec![self.initial_node()],
+            parents: vec![None],
+            best_g_costs,
+            open,
+            max_cost,
+            last: 0,
+        }
+    }
+
+    /// Counts the requirements the initial state doesn't fulfill.
+    #[cfg(feature = "std")]
+    fn unmet_count(&self, requirements: &Requirements) -> usize {
+        requirements.iter()
+            .filter(|&(&id, goal_atom)| {
+                !goal_atom.is_met_by(self.initial_values[id as usize].as_ref())
+            })
+            .count()
+    }
+}
+
+/// The costs of the cheapest ways found yet to the nodes reached by a search, as told by
+/// `SearchSpace::search_with_costs`.
+pub struct ReachedCosts(BTreeMap<Changes, usize>);
+
+impl ReachedCosts {
+    /// Records that a node was reached for `g_cost`, and returns whether that's cheaper than the
+    /// ways to it found before, if any.
+    pub fn reach<K: Atom>(&mut self, node: &PlanNode<K>, g_cost: usize) -> bool {
+        match self.0.get_mut(node.changes()) {
+            Some(best_g_cost) if *best_g_cost <= g_cost => false,
+            Some(best_g_cost) => {
+                *best_g_cost = g_cost;
+                true
+            }
+            None => {
+                self.0.insert(node.changes().clone(), g_cost);
+                true
+            }
+        }
+    }
+}
+
+/// A search going through the nodes of the states graph by increasing cost, as made by
+/// `SearchSpace::by_cost`, which returns each node along with the cost of the cheapest way to it.
+pub struct CostOrder<'s, 'a: 's, K: 's + 'a> {
+    nodes: Vec<PlanNode<'s, 'a, K>>,
+    /// The position of the node each node was reached from, along with the index of the action
+    /// applied to get there.
+    parents: Vec<Option<(usize, usize)>>,
+    best_g_costs: BTreeMap<Changes, usize>,
+    open: BinaryHeap<Reverse<(usize, usize)>>,
+    max_cost: usize,
+    last: usize,
+}
+
+impl<'s, 'a, K: Atom> CostOrder<'s, 'a, K> {
+    /// Returns the indices of the actions of the cheapest way to the node returned last, in order,
+    /// out of the actions the search was made from.
+    pub fn action_indices(&self) -> Vec<usize> {
+        let mut indices = vec![];
+        let mut position = self.last;
+        while let Some((parent, index)) = self.parents[position] {
+            indices.push(index);
+            position = parent;
+        }
+        indices.reverse();
+        indices
+    }
+}
+
+impl<'s, 'a, K: Atom> Iterator for CostOrder<'s, 'a, K> {
+    type Item = (PlanNode<'s, 'a, K>, usize);
+
+    fn next(&mut self) -> Option<(PlanNode<'s, 'a, K>, usize)> {
+        while let Some(Reverse((g_cost, index))) = self.open.pop() {
+            // Skips the nodes reached in a cheaper way since.
+            let node = self.nodes[index].clone();
+            if self.best_g_costs[node.changes()] < g_cost {
+                continue;
+            }
+
+            for (action_index, child, cost) in node.transitions() {
+                let child_g_cost = g_cost + cost;
+                let best_g_cost = self.best_g_costs.get(child.changes()).cloned();
+                if child_g_cost > self.max_cost ||
+                   best_g_cost.is_some_and(|best_g_cost| best_g_cost <= child_g_cost) {
+                    continue;
+                }
+                self.best_g_costs.insert(child.changes().clone(), child_g_cost);
+                self.open.push(Reverse((child_g_cost, self.nodes.len())));
+                self.nodes.push(child);
+                self.parents.push(Some((index, action_index)));
+            }
+
+            self.last = index;
+            return Some((node, g_cost));
+        }
+
+        None
+    }
+}
+
+/// Returns the actions applied along a path of nodes, out of the actions the search was made from.
+///
+/// Nodes don't remember which action led to them, since the same state can be reached in many
+/// ways, so the cheapest action leading from each node of the path to the next one is looked up.
+pub fn path_actions<'a, K, C>(path: &[PlanNode<K>],
+                              actions: &'a [Action<K, C>])
+                              -> Vec<&'a Action<K, C>>
+    where K: Atom
+{
+    path_action_indices(path).into_iter().map(|index| &actions[index]).collect()
+}
+
+/// Returns the indices of the actions applied along a path of nodes, like `path_actions` does.
+pub fn path_action_indices<K: Atom>(path: &[PlanNode<K>]) -> Vec<usize> {
+    path_steps(path).into_iter().map(|step| step.0).collect()
+}
+
+/// Returns the indices of the actions applied along a path of nodes, like `path_actions` does,
+/// along with the cost each of them had when applied.
+pub fn path_steps<K: Atom>(path: &[PlanNode<K>]) -> Vec<(usize, usize)> {
+    path.windows(2)
+        .map(|pair| {
+            let (indices, cost) = cheapest_actions(&pair[0], &pair[1]);
+            (indices[0], cost)
+        })
+        .collect()
+}
+
+/// Returns the indices of all the sequences of actions that can be applied along a path of nodes,
+/// when several actions are equally cheap to get from one node of the path to the next.
+#[cfg(feature = "std")]
+pub fn path_index_sequences<K: Atom>(path: &[PlanNode<K>]) -> Vec<Vec<usize>> {
+    let mut sequences = vec![vec![]];
+    for pair in path.windows(2) {
+        let (indices, _) = cheapest_actions(&pair[0], &pair[1]);
+        sequences = sequences.into_iter()
+            .flat_map(|sequence: Vec<usize>| {
+                indices.iter().map(move |&index| {
+                    let mut sequence = sequence.clone();
+                    sequence.push(index);
+                    sequence
+                })
+            })
+            .collect();
+    }
+
+    sequences
+}
+
+/// Returns the indices of the cheapest actions leading from a node to another, in the order actions
+/// are tried in, along with their cost.
+fn cheapest_actions<K: Atom>(from: &PlanNode<K>, to: &PlanNode<K>) -> (Vec<usize>, usize) {
+    let transitions: Vec<_> =
+        from.transitions().into_iter().filter(|transition| transition.1 == *to).collect();
+    let cheapest_cost = transitions.iter().map(|transition| transition.2).min().unwrap_or(0);
+
+    let indices = transitions.into_iter()
+        .filter(|transition| transition.2 == cheapest_cost)
+        .map(|(index, _, _)| index)
+        .collect();
+    (indices, cheapest_cost)
+}
+
+/// A node in the planner graph.
+///
+/// Rather than a full copy of the world state, each node only holds the atoms whose values differ
+/// from the initial state, which all nodes share. This keeps making a child node proportional to
+/// the number of atoms changed since the initial state, rather than to the size of the state.
+///
+/// Nodes from the same search share the same initial state, so the identity of a node only depends
+/// on these changes: the same state reached through different actions is the same node.
+#[derive(Clone)]
+pub struct PlanNode<'s, 'a: 's, K: 's + 'a> {
+    space: &'s SearchSpace<'a, K>,
+    changes: Changes,
+}
+
+impl<'s, 'a, K: Atom> PartialEq for PlanNode<'s, 'a, K> {
+    fn eq(&self, other: &PlanNode<'s, 'a, K>) -> bool {
+        self.changes == other.changes
+    }
+}
+
+impl<'s, 'a, K: Atom> Eq for PlanNode<'s, 'a, K> {}
+
+impl<'s, 'a, K: Atom> Hash for PlanNode<'s, 'a, K> {
+    fn hash<H>(&self, state: &mut H)
+        where H: Hasher
+    {
+        self.changes.hash(state);
+    }
+}
+
+impl<'s, 'a, K: Atom> PlanNode<'s, 'a, K> {
+    /// Makes a plan node from a parent node and the index of an action applied to its state.
+    #[cfg(test)]
+    fn child(parent: &PlanNode<'s, 'a, K>, index: usize) -> PlanNode<'s, 'a, K> {
+        parent.space.node(parent.view().apply(&parent.space.actions[index]))
+    }
+
+    /// Returns all possible nodes from this current state, along with the cost to get there.
+    pub fn possible_next_nodes(&self) -> Vec<(PlanNode<'s, 'a, K>, usize)> {
+        self.transitions().into_iter().map(|(_, node, cost)| (node, cost)).collect()
+    }
+
+    /// Returns all possible nodes from this current state, along with the index of the action
+    /// leading there among the actions the search was made from, and its cost.
+    pub fn transitions(&self) -> Vec<(usize, PlanNode<'s, 'a, K>, usize)> {
+        self.costed(self.applicable_children())
+    }
+
+    /// Returns the positions of the actions applicable to this current state among the actions of
+    /// the search, in the order actions are tried in.
+    #[cfg(feature = "std")]
+    pub fn applicable_positions(&self) -> Vec<usize> {
+        let view = self.view();
+        self.space
+            .order
+            .iter()
+            .cloned()
+            .filter(|&position| view.allows(&self.space.actions[position]))
+            .collect()
+    }
+
+    /// Returns all possible nodes from this current state like `transitions` does, given the
+    /// positions of the actions applicable to it, as returned by `applicable_positions`.
+    #[cfg(feature = "std")]
+    pub fn transitions_from(&self,
+                            positions: &[usize])
+                            -> Vec<(usize, PlanNode<'s, 'a, K>, usize)> {
+        let view = self.view();
+        let children = positions.iter()
+            .map(|&position| (position, view.apply(&self.space.actions[position])))
+            .collect();
+        self.costed(children)
+    }
+
+    /// Turns the children of this node, given by position of the action leading to them, into
+    /// transitions.
+    fn costed(&self, children: Vec<(usize, Changes)>) -> Vec<(usize, PlanNode<'s, 'a, K>, usize)> {
+        let mut current_state = None;
+        children.into_iter()
+            .map(|(index, changes)| {
+                // The current state only gets built when an action needs it to tell its cost.
+                let action = &self.space.actions[index];
+                let cost = match self.space.cost_fns[index] {
+                    Some(ref cost_fn) => {
+                        cost_fn(current_state.get_or_insert_with(|| self.current_state()))
+                    }
+                    None => action.cost,
+                };
+                (action.index, self.space.node(changes), cost)
+            })
+            .collect()
+    }
+
+    /// Returns the index of each action applicable to this current state, along with the changes
+    /// making up the state it leads to, in the order actions are tried in.
+    #[cfg(not(feature = "rayon"))]
+    fn applicable_children(&self) -> Vec<(usize, Changes)> {
+        let view = self.view();
+        self.space
+            .order
+            .iter()
+            .map(|&index| (index, &self.space.actions[index]))
+            .filter(|&(_, action)| view.allows(action))
+            .map(|(index, action)| (index, view.apply(action)))
+            .collect()
+    }
+
+    /// Returns the index of each action applicable to this current state, along with the changes
+    /// making up the state it leads to, in the order actions are tried in.
+    ///
+    /// Actions are checked and applied in parallel, and still come out in that order.
+    #[cfg(feature = "rayon")]
+    fn applicable_children(&self) -> Vec<(usize, Changes)> {
+        let view = self.view();
+        let actions = &self.space.actions;
+        self.space
+            .order
+            .par_iter()
+            .map(|&index| (index, &actions[index]))
+            .filter(|&(_, action)| view.allows(action))
+            .map(|(index, action)| (index, view.apply(action)))
+            .collect()
+    }
+
+    /// Returns a view of the current state of this node.
+    fn view<'n>(&'n self) -> StateView<'n> {
+        StateView {
+            changes: &self.changes,
+            initial_values: &self.space.initial_values,
+        }
+    }
+
+    /// Returns the value of an atom in the current state of this node.
+    fn get(&self, id: AtomId) -> Option<&Value> {
+        self.view().get(id)
+    }
+
+    /// Returns the changes from the initial state making up the state of this node.
+    pub fn changes(&self) -> &Changes {
+        &self.changes
+    }
+
+    /// Returns how many times the actions counted under a mark were applied to get to this node.
+    #[cfg(feature = "std")]
+    pub fn use_count(&self, mark: AtomId) -> usize {
+        match self.changes.get(&mark) {
+            Some(&Value::Int(count)) => count as usize,
+            _ => 0,
+        }
+    }
+
+    /// Counts one more use of the actions counted under a mark, and returns this node.
+    #[cfg(feature = "std")]
+    pub fn counted(mut self, mark: AtomId) -> PlanNode<'s, 'a, K> {
+        let count = self.use_count(mark) as i64 + 1;
+        self.changes.insert(mark, Value::Int(count));
+        self
+    }
+
+    /// Returns the atoms whose values differ from the initial state, along with their values.
+    #[cfg(feature = "std")]
+    pub fn changed_atoms(&self) -> Vec<(&K, Value)> {
+        self.changes
+            .range(..FIRST_USED_MARK)
+            .map(|(&id, &value)| (&self.space.names[id as usize], value))
+            .collect()
+    }
+
+    /// Builds a full copy of the current state of this node.
+    pub fn current_state(&self) -> State<K> {
+        let mut current_state = State::new();
+        for (id, name) in self.space.names.iter().enumerate() {
+            if let Some(value) = self.get(id as AtomId) {
+                current_state.insert(name.clone(), *value);
+            }
+        }
+
+        current_state
+    }
+
+    /// Count the number of states in this node that aren't matching the given target.
+    pub fn mismatch_count(&self, target: &SearchGoal) -> usize {
+        self.mismatch_count_with(target, OnMissing::CountAsMismatch)
+    }
+
+    /// Count the number of states in this node that aren't matching the given target like
+    /// `mismatch_count` does, with absent atoms handled as told by `on_missing`.
+    pub fn mismatch_count_with(&self, target: &SearchGoal, on_missing: OnMissing) -> usize {
+        let mut count: usize = 0;
+        for &(id, ref goal_atom) in target {
+            if !goal_atom.is_met_with(self.get(id), on_missing) {
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    /// Sums the weights of the atoms of this node that aren't matching the given target, given
+    /// in the same order as the atoms of the target.
+    pub fn weighted_mismatch_count(&self, target: &SearchGoal, weights: &[usize]) -> usize {
+        target.iter()
+            .zip(weights)
+            .filter(|&(&(id, ref goal_atom), _)| !goal_atom.is_met_by(self.get(id)))
+            .map(|(_, &weight)| weight)
+            .sum()
+    }
+
+    /// Returns `true` if the current node is a full match for the given target.
+    pub fn matches(&self, target: &SearchGoal) -> bool {
+        self.mismatch_count(target) == 0
+    }
+}
+
+/// The state of a node, without anything tied to the type of atoms, so that it can be shared
+/// between threads.
+#[derive(Clone, Copy)]
+struct StateView<'v> {
+    changes: &'v Changes,
+    initial_values: &'v [Option<Value>],
+}
+
+impl<'v> StateView<'v> {
+    /// Returns the value of an atom in this state.
+    fn get(&self, id: AtomId) -> Option<&'v Value> {
+        self.changes.get(&id).or_else(|| self.initial_values[id as usize].as_ref())
+    }
+
+    /// Returns `true` if this state fulfills all of the given conditions.
+    fn fulfills(&self, conditions: &[(AtomId, Condition)]) -> bool {
+        conditions.iter().all(|&(id, ref condition)| condition.is_met_by(self.get(id)))
+    }
+
+    /// Returns `true` if an action can be applied to this state.
+    fn allows(&self, action: &SearchAction) -> bool {
+        if action.used_marks.iter().any(|mark| self.changes.contains_key(mark)) {
+            return false;
+        }
+
+        self.fulfills(&action.pre_conditions) &&
+        action.relational_pre_conditions
+            .iter()
+            .all(|pre| pre.is_met_by(self.get(pre.left), self.get(pre.right))) &&
+        (action.any_of.is_empty() ||
+         action.any_of.iter().any(|alternative| self.fulfills(alternative)))
+    }
+
+    /// Returns the changes making up the state an action leads to from this state.
+    fn apply(&self, action: &SearchAction) -> Changes {
+        let mut changes = self.changes.clone();
+
+        // Only keeps track of the atoms that end up differing from the initial state.
+        for &(id, ref effect) in &action.post_conditions {
+            if let Some(value) = effect.apply_to(self.get(id)) {
+                if self.initial_values[id as usize] == Some(value) {
+                    changes.remove(&id);
+                } else {
+                    changes.insert(id, value);
+                }
+            }
+        }
+        for &mark in &action.used_marks {
+            changes.insert(mark, Value::Bool(true));
+        }
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generate::generate_domain;
+
+    #[test]
+    fn test_plan_node_changes() {
+        let actions = [Action::new("open", 1).produces("door_open", true),
+                       Action::new("close", 1).produces("door_open", false)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("door_open".to_string(), false.into());
+        initial_state.insert("lights_on".to_string(), true.into());
+
+        let mut closed_goal = State::new();
+        closed_goal.insert("door_open".to_string(), false.into());
+
+        let mut space = SearchSpace::new(&initial_state, &actions, &());
+        let goal = space.goal(&closed_goal);
+        let door_open = space.ids["door_open"];
+        let lights_on = space.ids["lights_on"];
+
+        // Only the atoms differing from the initial state are tracked by nodes.
+        let initial = space.initial_node();
+        let opened = PlanNode::child(&initial, 0);
+        assert_eq!(opened.changes.len(), 1);
+        assert_eq!(opened.get(door_open), Some(&Value::Bool(true)));
+        assert_eq!(opened.get(lights_on), Some(&Value::Bool(true)));
+
+        assert_eq!(opened.mismatch_count(&goal), 1);
+
+        // Setting an atom back to its initial value unsets the change.
+        let closed = PlanNode::child(&opened, 1);
+        assert!(closed.changes.is_empty());
+        assert_eq!(closed.current_state(), initial_state);
+        assert_eq!(closed.mismatch_count(&goal), 0);
+    }
+
+    #[test]
+    fn test_atom_interning() {
+        let actions = [Action::new("chop", 1).requires("has_axe", true).produces("wood", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("has_axe".to_string(), true.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("wood".to_string(), true.into());
+        goal_state.insert("fire".to_string(), true.into());
+
+        // Atoms get the same identifier wherever they're referred to from.
+        let mut space = SearchSpace::new(&initial_state, &actions, &());
+        let goal = space.goal(&goal_state);
+        assert_eq!(space.names, vec!["has_axe", "wood", "fire"]);
+        assert_eq!(goal, vec![(2, true.into()), (1, true.into())]);
+        assert_eq!(space.initial_values, vec![Some(Value::Bool(true)), None, None]);
+
+        // Atoms that are in no state aren't part of the current state either.
+        let node = space.initial_node();
+        assert_eq!(node.current_state(), initial_state);
+        assert_eq!(node.mismatch_count(&goal), 2);
+    }
+
+    #[test]
+    fn test_same_state_merges() {
+        // Walking and running both get to the door, from where the way is the same.
+        let actions = [Action::new("walk", 1).produces("at_door", true),
+                       Action::new("run", 1).produces("at_door", true),
+                       Action::new("open", 1)
+                           .requires("at_door", true)
+                           .produces("door_open", true),
+                       Action::new("enter", 1)
+                           .requires("door_open", true)
+                           .produces("inside", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("at_door".to_string(), false.into());
+        initial_state.insert("door_open".to_string(), false.into());
+        initial_state.insert("inside".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("inside".to_string(), true.into());
+
+        let mut space = SearchSpace::new(&initial_state, &actions, &());
+        let goal = space.goal(&goal_state);
+
+        let mut expanded_nodes = 0;
+        let (path, cost) = space.search_with(|node| {
+                                                 expanded_nodes += 1;
+                                                 node.possible_next_nodes()
+                                             },
+                                             |node| node.mismatch_count(&goal),
+                                             |node| node.matches(&goal))
+            .unwrap();
+
+        // Only one of the ways to the door was explored any further.
+        assert_eq!(expanded_nodes, 3);
+        assert_eq!(cost, 3);
+
+        let names: Vec<&str> =
+            path_actions(&path, &actions).iter().map(|action| action.name.as_str()).collect();
+        // Of the equally cheap ways to the door, the first one by name is the one told.
+        assert_eq!(names, vec!["run", "open", "enter"]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_search_biased() {
+        // Every action fixes one goal atom, so that all the ways to the goal cost as much as
+        // estimated from the start, and every node ties with every other.
+        let mut actions = vec![];
+        let mut goal_state = State::new();
+        for i in 0..6 {
+            actions.push(Action::new(format!("fix_{}", i), 1)
+                .produces(format!("atom_{}", i), true));
+            goal_state.insert(format!("atom_{}", i), true.into());
+        }
+
+        let initial_state = State::new();
+        let mut space = SearchSpace::new(&initial_state, &actions, &());
+        let goal = space.goal(&goal_state);
+
+        let expansions = |bias| {
+            let mut expanded_nodes = 0;
+            let (_, cost) = space.search_biased(bias,
+                                                |node| {
+                                                    expanded_nodes += 1;
+                                                    node.possible_next_nodes()
+                                                },
+                                                |node| node.mismatch_count(&goal),
+                                                |node| node.matches(&goal))
+                .unwrap();
+            assert_eq!(cost, 6);
+            expanded_nodes
+        };
+
+        // Heading for the goal goes straight there, while staying close to the start goes through
+        // every combination of fixed atoms first.
+        assert_eq!(expansions(TieBias::PreferGoal), 6);
+        assert_eq!(expansions(TieBias::PreferStart), 63);
+    }
+
+    #[test]
+    fn test_search_already_there() {
+        let actions = [Action::new("open", 1).produces("door_open", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("door_open".to_string(), true.into());
+
+        let mut space = SearchSpace::new(&initial_state, &actions, &());
+        let goal = space.goal(&initial_state);
+
+        // The goal state is reached without ever estimating or expanding anything.
+        let mut estimates = 0;
+        let result = space.search_with(|_| panic!("no node should be expanded"),
+                                       |node| {
+                                           estimates += 1;
+                                           node.mismatch_count(&goal)
+                                       },
+                                       |node| node.matches(&goal));
+        let (path, cost) = result.unwrap();
+        assert_eq!(path.len(), 1);
+        assert_eq!(cost, 0);
+        assert_eq!(estimates, 0);
+    }
+
+    #[test]
+    fn test_search_best_first() {
+        // The loop searching without the standard library expands nodes in the same order as A*,
+        // and so finds the same plans, even among many equally cheap ones.
+        let mut domains: Vec<_> = (0..20).map(|seed| generate_domain(10, 30, seed)).collect();
+        let mut tied_actions = vec![];
+        for tool in &["axe", "hammer", "knife"] {
+            tied_actions.push(Action::new(format!("get_{}", tool), 1).produces(*tool, true));
+            for material in &["bone", "stone", "wood"] {
+                tied_actions.push(Action::new(format!("get_{}", material), 1)
+                    .produces(*material, true));
+                tied_actions.push(Action::new(format!("craft_with_{}_{}", tool, material), 1)
+                    .requires(*tool, true)
+                    .requires(*material, true)
+                    .produces("has_item", true));
+            }
+        }
+        let mut goal_state = State::new();
+        goal_state.insert("has_item".to_string(), true.into());
+        domains.push((State::new(), goal_state, tied_actions));
+
+        for (initial_state, goal_state, actions) in domains {
+            let mut space = SearchSpace::new(&initial_state, &actions, &());
+            let goal = space.goal(&goal_state);
+            let (path, cost) = space.search_best_first(|node| node.possible_next_nodes(),
+                                                       |node| node.mismatch_count(&goal),
+                                                       |node| node.matches(&goal))
+                .unwrap();
+            let (expected_path, expected_cost) = space.search(|node| node.mismatch_count(&goal),
+                                                              |node| node.matches(&goal))
+                .unwrap();
+            assert_eq!(cost, expected_cost);
+            assert_eq!(path_action_indices(&path), path_action_indices(&expected_path));
+        }
+    }
+
+    #[test]
+    fn test_path_actions_cheapest() {
+        // When several actions lead to the same state, the cheapest one is the one used, the first
+        // by name among equally cheap ones.
+        let actions = [Action::new("run", 3).produces("at_door", true),
+                       Action::new("walk", 1).produces("at_door", true),
+                       Action::new("crawl", 1).produces("at_door", true)];
+
+        let mut initial_state = State::new();
+        initial_state.insert("at_door".to_string(), false.into());
+
+        let mut goal_state = State::new();
+        goal_state.insert("at_door".to_string(), true.into());
+
+        let mut space = SearchSpace::new(&initial_state, &actions, &());
+        let goal = space.goal(&goal_state);
+        let (path, cost) =
+            space.search(|node| node.mismatch_count(&goal), |node| node.matches(&goal)).unwrap();
+        assert_eq!(cost, 1);
+        assert_eq!(path_actions(&path, &actions)[0].name, "crawl");
+    }
+}
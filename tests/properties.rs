@@ -0,0 +1,56 @@
+// Copyright 2017 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Properties which plans hold for any domain, checked over generated domains.
+
+extern crate quickcheck;
+extern crate rgoap;
+
+use quickcheck::{QuickCheck, TestResult};
+use rgoap::{Action, State, apply, generate_domain, plan_with_cost};
+
+/// Generates the domain matching some arbitrary numbers, with up to 16 atoms and 48 actions.
+fn domain(num_atoms: u8, num_actions: u8, seed: u64) -> (State, State, Vec<Action>) {
+    generate_domain(1 + num_atoms as usize % 16, 1 + num_actions as usize % 48, seed)
+}
+
+#[test]
+fn plans_reach_the_goal() {
+    fn property(num_atoms: u8, num_actions: u8, seed: u64) -> TestResult {
+        let (initial_state, goal_state, actions) = domain(num_atoms, num_actions, seed);
+        let planned = match plan_with_cost(&initial_state, &goal_state, &actions) {
+            Some(planned) => planned,
+            None => return TestResult::error("generated domains always have a plan"),
+        };
+
+        // Each action is applicable when it's applied, and the last one leads to the goal.
+        let mut state = initial_state.clone();
+        for action in &planned.actions {
+            let is_applicable = action.pre_conditions
+                .iter()
+                .all(|(name, condition)| condition.is_met_by(state.get(name)));
+            if !is_applicable {
+                return TestResult::failed();
+            }
+            apply(action, &mut state);
+        }
+        let is_reached = goal_state.iter().all(|(name, value)| state.get(name) == Some(value));
+        TestResult::from_bool(is_reached)
+    }
+
+    QuickCheck::new().quickcheck(property as fn(u8, u8, u64) -> TestResult);
+}
+
+#[test]
+fn plan_costs_add_up() {
+    fn property(num_atoms: u8, num_actions: u8, seed: u64) -> bool {
+        let (initial_state, goal_state, actions) = domain(num_atoms, num_actions, seed);
+        let planned = plan_with_cost(&initial_state, &goal_state, &actions).unwrap();
+
+        // Generated actions have static costs, which are the costs of the steps.
+        let static_costs: Vec<usize> = planned.actions.iter().map(|action| action.cost).collect();
+        planned.step_costs == static_costs &&
+        planned.step_costs.iter().sum::<usize>() == planned.cost
+    }
+
+    QuickCheck::new().quickcheck(property as fn(u8, u8, u64) -> bool);
+}